@@ -1,30 +1,65 @@
 use anyhow::{Result, bail};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
 pub fn find_rust_files(path: &str) -> Result<Vec<String>> {
+    find_files(path, "rs")
+}
+
+pub fn find_markdown_files(path: &str) -> Result<Vec<String>> {
+    find_files(path, "md")
+}
+
+fn find_files(path: &str, file_extension: &str) -> Result<Vec<String>> {
     let path = Path::new(path);
-    let mut rust_files = Vec::new();
+    let mut files = Vec::new();
 
     if path.is_file() {
         if let Some(extension) = path.extension()
-            && extension == "rs"
+            && extension == file_extension
         {
-            rust_files.push(path.to_string_lossy().to_string());
+            files.push(path.to_string_lossy().to_string());
         }
     } else if path.is_dir() {
         for entry in WalkDir::new(path).into_iter().filter_map(|e| e.ok()) {
             let file_path = entry.path();
             if file_path.is_file()
                 && let Some(extension) = file_path.extension()
-                && extension == "rs"
+                && extension == file_extension
             {
-                rust_files.push(file_path.to_string_lossy().to_string());
+                files.push(file_path.to_string_lossy().to_string());
             }
         }
     } else {
         bail!("path '{}' does not exist", path.display());
     }
 
-    Ok(rust_files)
+    Ok(files)
+}
+
+/// Finds the directory of the closest `Cargo.toml` above `path`, i.e. its `CARGO_MANIFEST_DIR`
+pub fn find_manifest_dir(path: &Path) -> Option<PathBuf> {
+    let path = path.canonicalize().ok()?;
+
+    path.ancestors()
+        .find(|dir| dir.join("Cargo.toml").is_file())
+        .map(Path::to_path_buf)
+}
+
+/// Finds the `.sql` files directly inside `dir`, e.g. `0001_users.sql` or `0002_orders.up.sql`
+pub fn find_sql_files_in_dir(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut sql_files = Vec::new();
+
+    for entry in std::fs::read_dir(dir)? {
+        let file_path = entry?.path();
+        if file_path.is_file()
+            && let Some(extension) = file_path.extension()
+            && extension == "sql"
+        {
+            sql_files.push(file_path);
+        }
+    }
+    sql_files.sort();
+
+    Ok(sql_files)
 }
@@ -1,12 +1,20 @@
 pub mod formatter;
 pub mod fs;
+pub mod markdown;
+pub mod pattern;
+pub mod region;
+pub mod sentinel;
+pub mod template;
 pub mod tree_sitter;
 
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
+use log::{debug, error, warn};
+use pattern::{Matcher, Pattern};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tree_sitter::SqlReference;
 
-const DEFAULT_MACROS: [&str; 14] = [
-    "migrate",
-    "sqlx::migrate",
+const DEFAULT_MACROS: [&str; 12] = [
     "query",
     "sqlx::query",
     "query_unchecked",
@@ -21,34 +29,420 @@ const DEFAULT_MACROS: [&str; 14] = [
     "sqlx::query_scalar_unchecked",
 ];
 
+const DEFAULT_FUNCTIONS: [&str; 14] = [
+    "query",
+    "sqlx::query",
+    "query_with",
+    "sqlx::query_with",
+    "query_as",
+    "sqlx::query_as",
+    "query_as_with",
+    "sqlx::query_as_with",
+    "query_scalar",
+    "sqlx::query_scalar",
+    "query_scalar_with",
+    "sqlx::query_scalar_with",
+    "raw_sql",
+    "sqlx::raw_sql",
+];
+
+const DEFAULT_QUERY_FILE_MACROS: [&str; 12] = [
+    "query_file",
+    "sqlx::query_file",
+    "query_file_unchecked",
+    "sqlx::query_file_unchecked",
+    "query_file_as",
+    "sqlx::query_file_as",
+    "query_file_as_unchecked",
+    "sqlx::query_file_as_unchecked",
+    "query_file_scalar",
+    "sqlx::query_file_scalar",
+    "query_file_scalar_unchecked",
+    "sqlx::query_file_scalar_unchecked",
+];
+
+const DEFAULT_MIGRATE_MACROS: [&str; 2] = ["migrate", "sqlx::migrate"];
+
+const DEFAULT_TEST_ATTRIBUTES: [&str; 1] = ["sqlx::test"];
+
+const DEFAULT_TEMPLATE_MACROS: [&str; 3] = ["format@0", "std::format@0", "alloc::format@0"];
+
+/// Functions of other database crates as `(name, functions)`,
+/// methods are matched by their name prefixed with a dot, e.g. `.execute`
+const PRESETS: [(&str, &[&str]); 5] = [
+    (
+        "tokio-postgres",
+        &[
+            ".query@0",
+            ".query_one@0",
+            ".query_opt@0",
+            ".query_raw@0",
+            ".query_typed@0",
+            ".execute@0",
+            ".execute_raw@0",
+            ".batch_execute@0",
+            ".simple_query@0",
+            ".prepare@0",
+            ".prepare_typed@0",
+            "tokio_postgres::Client::*@1",
+            "tokio_postgres::Transaction::*@1",
+        ],
+    ),
+    (
+        "postgres",
+        &[
+            ".query@0",
+            ".query_one@0",
+            ".query_opt@0",
+            ".query_raw@0",
+            ".execute@0",
+            ".batch_execute@0",
+            ".simple_query@0",
+            ".prepare@0",
+            ".prepare_typed@0",
+            "postgres::Client::*@1",
+            "postgres::Transaction::*@1",
+        ],
+    ),
+    (
+        "rusqlite",
+        &[
+            ".execute@0",
+            ".execute_batch@0",
+            ".prepare@0",
+            ".prepare_cached@0",
+            ".query_row@0",
+            ".query_one@0",
+            "rusqlite::Connection::*@1",
+            "rusqlite::Transaction::*@1",
+        ],
+    ),
+    ("diesel", &["sql_query@0", "diesel::sql_query@0"]),
+    (
+        "sea-orm",
+        &[
+            "Statement::from_string@1",
+            "sea_orm::Statement::from_string@1",
+            "Statement::from_sql_and_values@1",
+            "sea_orm::Statement::from_sql_and_values@1",
+            ".execute_unprepared@0",
+        ],
+    ),
+];
+
+/// Options for formatting sql in a rust file
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Path to sqruff config file
+    pub config: String,
+    /// Literal indentation level, should match `tab_space_size` used in your .sqruff config
+    pub literal_indentation: usize,
+    /// Indent the sql of multi-line literals with spaces or tabs
+    pub indent_style: tree_sitter::IndentStyle,
+    /// Columns a tab takes up when aligning sql with the code in front of a literal
+    pub tab_width: usize,
+    /// Layout of multi-line raw string literals
+    pub layout: tree_sitter::Layout,
+    /// Write raw string literals on one line if their sql, joined to one line, ends within this column
+    pub single_line_max_width: Option<usize>,
+    /// Macros to format, comma separated, defaults to the sqlx query macros.
+    /// Globs like `*::query*` and regexes like `re:^tenant_.*query$` are supported.
+    /// Only the argument at a position or with a name is formatted for entries like
+    /// `cached_query@1` or `audit_query@sql`, instead of all string literals.
+    pub macros: Option<String>,
+    /// Functions to format, comma separated, defaults to the sqlx query functions.
+    /// Supports the same patterns as `macros`.
+    pub functions: Option<String>,
+    /// Paths to tree-sitter query files, comma separated, whose `@sql` captures are formatted
+    pub tree_sitter_queries: Option<String>,
+    /// Also format sql in the rust code blocks of `///` and `//!` doc comments
+    pub doc_comments: bool,
+    /// Also format sql in the ```rust and ```sql code blocks of markdown files
+    pub markdown: bool,
+    /// Also format the templates of `format!` passed to sqlx functions,
+    /// e.g. `sqlx::query(&format!("select {cols} from {table}"))`
+    pub format_templates: bool,
+    /// How sql spanning multiple lines is written back into normal string literals
+    pub string_style: tree_sitter::StringStyle,
+    /// Presets for other database crates, comma separated, e.g. `tokio-postgres, rusqlite`
+    pub presets: Option<String>,
+    /// Also format sql in the bodies of `macro_rules!` definitions, `$ident` metavariables are left as is
+    pub macro_rules: bool,
+    /// Only format literals overlapping one of these ranges, e.g. lines `120-180`, all if empty
+    pub ranges: Vec<tree_sitter::SourceRange>,
+    /// Also format the migrations of `sqlx::migrate!()`, this changes the checksums of applied migrations
+    pub migrations: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            config: ".sqruff".to_string(),
+            literal_indentation: 4,
+            indent_style: Default::default(),
+            tab_width: 4,
+            layout: Default::default(),
+            single_line_max_width: None,
+            macros: None,
+            functions: None,
+            tree_sitter_queries: None,
+            doc_comments: false,
+            markdown: false,
+            format_templates: false,
+            string_style: Default::default(),
+            presets: None,
+            macro_rules: false,
+            ranges: Vec::new(),
+            migrations: false,
+        }
+    }
+}
+
 pub fn format(
     content: &str,
     config: &str,
     literal_indentation: usize,
     macros: &Option<String>,
 ) -> Result<String> {
-    let macros = macros.clone().unwrap_or(DEFAULT_MACROS.join(", "));
+    format_with_options(
+        content,
+        &Options {
+            config: config.to_string(),
+            literal_indentation,
+            macros: macros.clone(),
+            ..Default::default()
+        },
+    )
+}
 
-    let macros: Vec<String> = macros
-        .split(',')
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
+pub fn format_with_options(content: &str, options: &Options) -> Result<String> {
+    format_file(content, options).map(|formatted| formatted.source)
+}
+
+/// Like `format_with_options`, but also returns the sql left as is because it is
+/// in or next to a syntax error, e.g. to report it
+pub fn format_file(content: &str, options: &Options) -> Result<tree_sitter::Formatted> {
+    let settings = settings(options)?;
+
+    let res =
+        tree_sitter::format_query_macros(content, &settings, |sql, _is_raw| sqruff(sql, options));
+
+    check_syntax_errors(content, &res)?;
+
+    Ok(res)
+}
+
+/// Formats the sqlx macros in ```rust and the sql in ```sql code blocks of a markdown file
+pub fn format_markdown(content: &str, options: &Options) -> Result<String> {
+    let settings = settings(options)?;
+
+    let res = markdown::format_code_blocks(
+        content,
+        |code| {
+            let formatted =
+                tree_sitter::format_code_block_literals(code, &settings, |sql, _is_raw| {
+                    sqruff(sql, options)
+                });
+            match check_syntax_errors(code, &formatted) {
+                Ok(()) => formatted.source,
+                Err(e) => {
+                    error!("leaving rust code block as is, error: {e:?}");
+                    code.to_string()
+                }
+            }
+        },
+        |sql| sqruff(sql, options),
+    );
+
+    Ok(res)
+}
+
+/// Formats the content of a `.sql` file
+pub fn format_sql(content: &str, options: &Options) -> Result<String> {
+    if content.trim().is_empty() {
+        return Ok(content.to_string());
+    }
+
+    sqruff(content, options)
+}
+
+/// Checks the options up front, e.g. for an unknown preset or an invalid `re:` pattern,
+/// so they are not reported again for every file
+pub fn validate_options(options: &Options) -> Result<()> {
+    settings(options).map(|_| ())
+}
+
+/// Makes sure formatting did not introduce syntax errors, e.g. by replacing the wrong bytes.
+/// Errors outside of the replaced sql have to be at the same place as before formatting.
+fn check_syntax_errors(content: &str, formatted: &tree_sitter::Formatted) -> Result<()> {
+    if content == formatted.source {
+        return Ok(());
+    }
+
+    let errors: HashSet<usize> = tree_sitter::syntax_errors(content)
+        .iter()
+        .map(|error| error.start_byte)
+        .collect();
+    let introduced_errors: Vec<_> = tree_sitter::syntax_errors(&formatted.source)
+        .into_iter()
+        .filter(|error| {
+            formatted
+                .original_offset(error.start_byte)
+                .is_none_or(|offset| !errors.contains(&offset))
+        })
         .collect();
 
-    if macros.is_empty() {
-        bail!("no macros like 'query_as, sqlx::query, migrate' specified for formatting");
+    if !introduced_errors.is_empty() {
+        bail!(
+            "formatting introduced syntax errors at line(s) {}",
+            introduced_errors
+                .iter()
+                .map(|error| (error.start_point.row + 1).to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+/// Formats sql with sqruff, leaving `-- sqlx-fmt: off` / `-- sqlx-fmt: on` regions untouched
+fn sqruff(sql: &str, options: &Options) -> Result<String> {
+    region::format(sql, |sql| formatter::sqruff(sql, &options.config))
+}
+
+/// Finds the `.sql` files referenced in `content`, e.g. by `sqlx::query_file!("queries/user.sql")`,
+/// the migrations in the directory of `sqlx::migrate!()` if enabled or `#[sqlx::test(fixtures("users"))]`.
+/// Like sqlx, paths are resolved relative to the manifest dir of the crate `file_path` belongs to,
+/// fixtures relative to `file_path` itself.
+pub fn find_sql_files(content: &str, file_path: &Path, options: &Options) -> Result<Vec<PathBuf>> {
+    let settings = settings(options)?;
+    let manifest_dir = fs::find_manifest_dir(file_path);
+
+    let mut sql_files = Vec::new();
+
+    for reference in tree_sitter::find_sql_references(content, &settings) {
+        let sql_path = match &reference {
+            SqlReference::QueryFile(path) => manifest_dir.as_ref().map(|dir| dir.join(path)),
+            SqlReference::Migrations(_) if !options.migrations => {
+                debug!("skipping {reference:?}, migrations are not enabled");
+                continue;
+            }
+            SqlReference::Migrations(path) => manifest_dir
+                .as_ref()
+                .map(|dir| dir.join(path.as_deref().unwrap_or("./migrations"))),
+            SqlReference::Fixture(path) => file_path.parent().map(|dir| dir.join(path)),
+        };
+        let Some(sql_path) = sql_path else {
+            warn!(
+                "no Cargo.toml found for {}, skipping {reference:?}",
+                file_path.display()
+            );
+            continue;
+        };
+
+        if let SqlReference::Migrations(_) = reference {
+            if sql_path.is_dir() {
+                sql_files.extend(fs::find_sql_files_in_dir(&sql_path)?);
+            } else {
+                warn!(
+                    "migrations directory {} referenced in {} not found",
+                    sql_path.display(),
+                    file_path.display()
+                );
+            }
+        } else if sql_path.is_file() {
+            sql_files.push(sql_path);
+        } else {
+            warn!(
+                "sql file {} referenced in {} not found",
+                sql_path.display(),
+                file_path.display()
+            );
+        }
     }
 
+    sql_files.sort();
+    sql_files.dedup();
+
+    Ok(sql_files)
+}
+
+fn settings(options: &Options) -> Result<tree_sitter::Settings> {
+    let macros = options.macros.clone().unwrap_or(DEFAULT_MACROS.join(", "));
+    let macros = parse_matchers(&macros)?;
+
     if macros.is_empty() {
-        bail!("no macros like 'query_as, sqlx::query, migrate' specified for formatting");
+        bail!("no macros like 'query_as, sqlx::query' specified for formatting");
     }
 
-    let res = tree_sitter::format_query_macros_literals(
-        content,
-        literal_indentation,
-        macros,
-        |sql, _is_raw| formatter::sqruff(sql, config),
-    );
+    let functions = options
+        .functions
+        .clone()
+        .unwrap_or(DEFAULT_FUNCTIONS.join(", "));
+    let mut functions = parse_matchers(&functions)?;
 
-    Ok(res)
+    for preset in options
+        .presets
+        .iter()
+        .flat_map(|presets| presets.split(','))
+        .map(|preset| preset.trim())
+        .filter(|preset| !preset.is_empty())
+    {
+        let Some((_, preset_functions)) = PRESETS.iter().find(|(name, _)| *name == preset) else {
+            bail!(
+                "unknown preset '{preset}', available presets: {}",
+                PRESETS.map(|(name, _)| name).join(", ")
+            );
+        };
+        functions.extend(parse_matchers(&preset_functions.join(", "))?);
+    }
+
+    let template_macros = if options.format_templates {
+        parse_matchers(&DEFAULT_TEMPLATE_MACROS.join(", "))?
+    } else {
+        Vec::new()
+    };
+
+    let mut queries = Vec::new();
+    for path in options
+        .tree_sitter_queries
+        .iter()
+        .flat_map(|paths| paths.split(','))
+        .map(|path| path.trim())
+        .filter(|path| !path.is_empty())
+    {
+        let query = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read tree-sitter query file {path}"))?;
+        let query = tree_sitter::parse_query(&query)
+            .with_context(|| format!("invalid tree-sitter query in {path}"))?;
+        queries.push(query);
+    }
+
+    Ok(tree_sitter::Settings {
+        literal_indentation: options.literal_indentation,
+        indent_style: options.indent_style,
+        tab_width: options.tab_width,
+        layout: options.layout,
+        single_line_max_width: options.single_line_max_width,
+        macro_names: macros,
+        function_names: functions,
+        query_file_macro_names: DEFAULT_QUERY_FILE_MACROS.map(Pattern::from).to_vec(),
+        migrate_macro_names: DEFAULT_MIGRATE_MACROS.map(Pattern::from).to_vec(),
+        test_attribute_names: DEFAULT_TEST_ATTRIBUTES.map(Pattern::from).to_vec(),
+        queries,
+        doc_comments: options.doc_comments,
+        template_macro_names: template_macros,
+        string_style: options.string_style,
+        macro_rules: options.macro_rules,
+        ranges: options.ranges.clone(),
+    })
+}
+
+fn parse_matchers(list: &str) -> Result<Vec<Matcher>> {
+    list.split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(Matcher::parse)
+        .collect()
 }
@@ -1,8 +1,12 @@
 use anyhow::{Result, bail};
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use log::{error, info, warn};
-use sqlx_fmt::fs::find_rust_files;
+use sqlx_fmt::Options;
+use sqlx_fmt::fs::{find_markdown_files, find_rust_files};
+use sqlx_fmt::tree_sitter::{IndentStyle, Layout, SourceRange, StringStyle};
+use std::collections::BTreeSet;
 use std::fs;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(name = "sqlx-fmt")]
@@ -16,44 +20,117 @@ struct Cli {
 enum Commands {
     /// Format SQL in sqlx macros in Rust files
     Format {
-        /// Directory or file path to format
-        #[arg(long, default_value = ".", env = "SQLX_FMT_PATH")]
-        path: String,
-
-        /// Path to sqruff config file
-        #[arg(long, default_value = ".sqruff", env = "SQLX_FMT_SQRUFF_CONFIG")]
-        config: String,
-
-        /// Literal indentation level, should match `tab_space_size` used in your .sqruff config
-        #[arg(long, default_value = "4", env = "SQLX_FMT_LITERAL_INDENTATION")]
-        literal_indentation: usize,
-
-        /// Macros to format, comma separated
-        #[arg(long, env = "SQLX_FMT_MACROS")]
-        macros: Option<String>,
+        #[command(flatten)]
+        args: FormatArgs,
     },
     /// Check SQL formatting in sqlx macros in Rust files
     Check {
-        /// Directory or file path to check
-        #[arg(long, default_value = ".", env = "SQLX_FMT_PATH")]
-        path: String,
-
-        /// Path to sqruff config file
-        #[arg(long, default_value = ".sqruff", env = "SQLX_FMT_SQRUFF_CONFIG")]
-        config: String,
+        #[command(flatten)]
+        args: FormatArgs,
 
         /// Fail if any unformatted files are found (default is true)
         #[arg(long, default_value = "true", env = "SQLX_FMT_FAIL_ON_UNFORMATTED")]
         fail_on_unformatted: bool,
+    },
+}
 
-        /// Literal indentation level, should match `tab_space_size` used in your .sqruff config
-        #[arg(long, default_value = "4", env = "SQLX_FMT_LITERAL_INDENTATION")]
-        literal_indentation: usize,
+#[derive(Args)]
+struct FormatArgs {
+    /// Directory or file path to format or check
+    #[arg(long, default_value = ".", env = "SQLX_FMT_PATH")]
+    path: String,
 
-        /// Macros to format, comma separated, e.g. "query, query_as, sqlx::query, sqlx::query_as"
-        #[arg(long, env = "SQLX_FMT_MACROS")]
-        macros: Option<String>,
-    },
+    /// Path to sqruff config file
+    #[arg(long, default_value = ".sqruff", env = "SQLX_FMT_SQRUFF_CONFIG")]
+    config: String,
+
+    /// Literal indentation level, should match `tab_space_size` used in your .sqruff config
+    #[arg(long, default_value = "4", env = "SQLX_FMT_LITERAL_INDENTATION")]
+    literal_indentation: usize,
+
+    /// Indent the sql of multi-line literals with "spaces" (`--literal-indentation` per level) or "tabs"
+    #[arg(long, default_value = "spaces", env = "SQLX_FMT_INDENT_STYLE")]
+    indent_style: IndentStyle,
+
+    /// Columns a tab takes up when aligning sql with the code in front of a literal
+    #[arg(long, default_value = "4", env = "SQLX_FMT_TAB_WIDTH")]
+    tab_width: usize,
+
+    /// Layout of multi-line raw string literals, "block", "aligned", "hanging" or "macro-relative"
+    #[arg(long, default_value = "block", env = "SQLX_FMT_LAYOUT")]
+    layout: Layout,
+
+    /// Write raw string literals on one line, e.g. r#"select 1"#, if their sql joined to one line ends within this column
+    #[arg(long, env = "SQLX_FMT_SINGLE_LINE_MAX_WIDTH")]
+    single_line_max_width: Option<usize>,
+
+    /// Macros to format, comma separated, e.g. "sqlx::query, sqlx::query_as", globs like "*::query*" and regexes like "re:^tenant_.*query$" are supported, "cached_query@1" or "audit_query@sql" only format the given argument
+    #[arg(long, env = "SQLX_FMT_MACROS")]
+    macros: Option<String>,
+
+    /// Functions whose string literal arguments are formatted, comma separated, e.g. "sqlx::query, sqlx::query_as"
+    #[arg(long, env = "SQLX_FMT_FUNCTIONS")]
+    functions: Option<String>,
+
+    /// Tree-sitter query files (.scm) whose `@sql` captures are formatted, comma separated
+    #[arg(long, env = "SQLX_FMT_TREE_SITTER_QUERIES")]
+    tree_sitter_queries: Option<String>,
+
+    /// Also format sql in rust code examples of `///` and `//!` doc comments
+    #[arg(long, env = "SQLX_FMT_DOC_COMMENTS")]
+    doc_comments: bool,
+
+    /// Also format sql in ```rust and ```sql code blocks of markdown files
+    #[arg(long, env = "SQLX_FMT_MARKDOWN")]
+    markdown: bool,
+
+    /// Also format the templates of `format!` passed to sqlx functions, keeping `{}` placeholders
+    #[arg(long, env = "SQLX_FMT_FORMAT_TEMPLATES")]
+    format_templates: bool,
+
+    /// How multi-line sql is written back into normal string literals, "single-line" or "continuation"
+    #[arg(long, default_value = "single-line", env = "SQLX_FMT_STRING_STYLE")]
+    string_style: StringStyle,
+
+    /// Presets for other database crates, comma separated, e.g. "tokio-postgres, rusqlite, diesel, sea-orm"
+    #[arg(long, env = "SQLX_FMT_PRESET")]
+    preset: Option<String>,
+
+    /// Also format sql in the bodies of `macro_rules!` definitions, keeping `$ident` metavariables
+    #[arg(long, env = "SQLX_FMT_MACRO_RULES")]
+    macro_rules: bool,
+
+    /// Only format sql overlapping these lines of a rust file, e.g. "120-180", repeatable, referenced .sql and markdown files are skipped
+    #[arg(long, value_delimiter = ',', env = "SQLX_FMT_LINES")]
+    lines: Vec<SourceRange>,
+
+    /// Also format the migrations of `sqlx::migrate!()`, changes the checksums of already applied migrations
+    #[arg(long, env = "SQLX_FMT_MIGRATIONS")]
+    migrations: bool,
+}
+
+impl FormatArgs {
+    fn options(&self) -> Options {
+        Options {
+            config: self.config.clone(),
+            literal_indentation: self.literal_indentation,
+            indent_style: self.indent_style,
+            tab_width: self.tab_width,
+            layout: self.layout,
+            single_line_max_width: self.single_line_max_width,
+            macros: self.macros.clone(),
+            functions: self.functions.clone(),
+            tree_sitter_queries: self.tree_sitter_queries.clone(),
+            doc_comments: self.doc_comments,
+            markdown: self.markdown,
+            format_templates: self.format_templates,
+            string_style: self.string_style,
+            presets: self.preset.clone(),
+            macro_rules: self.macro_rules,
+            ranges: self.lines.clone(),
+            migrations: self.migrations,
+        }
+    }
 }
 
 fn main() {
@@ -64,31 +141,17 @@ fn main() {
     let cli = Cli::parse();
 
     match &cli.command {
-        Commands::Format {
-            path,
-            config,
-            literal_indentation,
-            macros,
-        } => {
-            if let Err(err) = format_files(path, config, *literal_indentation, macros) {
+        Commands::Format { args } => {
+            if let Err(err) = format_files(&args.path, &args.options()) {
                 error!("error: {err:?}");
                 std::process::exit(1);
             }
         }
         Commands::Check {
-            path,
-            config,
+            args,
             fail_on_unformatted,
-            literal_indentation,
-            macros,
         } => {
-            if let Err(err) = check_files(
-                path,
-                config,
-                *fail_on_unformatted,
-                *literal_indentation,
-                macros,
-            ) {
+            if let Err(err) = check_files(&args.path, &args.options(), *fail_on_unformatted) {
                 error!("error: {err:?}",);
                 std::process::exit(1);
             }
@@ -96,26 +159,79 @@ fn main() {
     }
 }
 
-fn format_files(
-    path: &str,
-    config: &str,
-    literal_indentation: usize,
-    _macros: &Option<String>,
-) -> Result<()> {
-    info!("formatting files in {path}, with config at {config}");
+/// Finds the rust files and, if enabled, the markdown files in `path`
+fn find_files(path: &str, options: &Options) -> Result<Vec<String>> {
+    let mut files = find_rust_files(path)?;
+    if options.markdown {
+        files.extend(find_markdown_files(path)?);
+    }
+
+    Ok(files)
+}
+
+/// Finds the sql files referenced by a rust file, none for markdown files or line ranges
+fn find_sql_files(file_path: &str, content: &str, options: &Options) -> Result<Vec<PathBuf>> {
+    if file_path.ends_with(".md") || !options.ranges.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    sqlx_fmt::find_sql_files(content, Path::new(file_path), options)
+}
 
-    let rust_files = find_rust_files(path)?;
+/// Formats a rust or markdown file
+fn format_content(file_path: &str, content: &str, options: &Options) -> Result<String> {
+    // line ranges only apply to the sql literals of rust files
 
-    if rust_files.is_empty() {
+    if file_path.ends_with(".md") {
+        if !options.ranges.is_empty() {
+            return Ok(content.to_string());
+        }
+        return sqlx_fmt::format_markdown(content, options);
+    }
+
+    let formatted = sqlx_fmt::format_file(content, options)?;
+    for literal in &formatted.skipped {
+        warn!(
+            "{file_path}:{}: sql next to a syntax error is not formatted",
+            literal.start_point.row + 1
+        );
+    }
+
+    Ok(formatted.source)
+}
+
+fn format_files(path: &str, options: &Options) -> Result<()> {
+    info!(
+        "formatting files in {path}, with config at {}",
+        options.config
+    );
+
+    sqlx_fmt::validate_options(options)?;
+    let files = find_files(path, options)?;
+
+    if files.is_empty() {
         println!("no rust files found in {}", path);
         return Ok(());
     }
 
     let mut formatted_count = 0;
+    let mut failed_count = 0;
+    let mut sql_files = BTreeSet::new();
 
-    for file_path in rust_files {
+    for file_path in files {
         let content = fs::read_to_string(&file_path)?;
-        let formatted_content = sqlx_fmt::format(&content, config, literal_indentation, _macros)?;
+        sql_files.extend(find_sql_files(&file_path, &content, options)?);
+
+        // a file that formatting would introduce syntax errors into is left as is,
+        // without stopping the others from being formatted
+        let formatted_content = match format_content(&file_path, &content, options) {
+            Ok(formatted_content) => formatted_content,
+            Err(err) => {
+                error!("{file_path}: {err:?}");
+                failed_count += 1;
+                continue;
+            }
+        };
 
         if content != formatted_content {
             fs::write(&file_path, formatted_content)?;
@@ -124,9 +240,20 @@ fn format_files(
         }
     }
 
-    if formatted_count == 0 {
+    for file_path in sql_files {
+        let content = fs::read_to_string(&file_path)?;
+        let formatted_content = sqlx_fmt::format_sql(&content, options)?;
+
+        if content != formatted_content {
+            fs::write(&file_path, formatted_content)?;
+            info!("formatted: {}", file_path.display());
+            formatted_count += 1;
+        }
+    }
+
+    if formatted_count == 0 && failed_count == 0 {
         info!("all files are already formatted correctly");
-    } else {
+    } else if formatted_count > 0 {
         info!(
             "formatted {} file{}",
             formatted_count,
@@ -134,30 +261,44 @@ fn format_files(
         );
     }
 
+    if failed_count > 0 {
+        bail!(
+            "failed to format {failed_count} file{}",
+            if failed_count > 1 { "s" } else { "" }
+        );
+    }
+
     Ok(())
 }
 
-fn check_files(
-    path: &str,
-    config: &str,
-    fail_on_unformatted: bool,
-    literal_indentation: usize,
-    macros: &Option<String>,
-) -> Result<()> {
-    info!("checking files in {path}, with config at {config}");
-    let rust_files = find_rust_files(path)?;
+fn check_files(path: &str, options: &Options, fail_on_unformatted: bool) -> Result<()> {
+    info!(
+        "checking files in {path}, with config at {}",
+        options.config
+    );
+
+    sqlx_fmt::validate_options(options)?;
+    let files = find_files(path, options)?;
 
-    if rust_files.is_empty() {
+    if files.is_empty() {
         warn!("no rust files found in {}", path);
         return Ok(());
     }
 
     let mut unformatted_count = 0;
+    let mut sql_files = BTreeSet::new();
 
-    for file_path in rust_files {
+    for file_path in files {
         let content = fs::read_to_string(&file_path)?;
-        let formatted = sqlx_fmt::format(&content, config, literal_indentation, macros)?;
-        let is_formatted = content == formatted;
+        sql_files.extend(find_sql_files(&file_path, &content, options)?);
+
+        let is_formatted = match format_content(&file_path, &content, options) {
+            Ok(formatted) => content == formatted,
+            Err(err) => {
+                error!("{file_path}: {err:?}");
+                false
+            }
+        };
 
         if !is_formatted {
             info!("unformatted: {file_path}");
@@ -165,6 +306,17 @@ fn check_files(
         }
     }
 
+    for file_path in sql_files {
+        let content = fs::read_to_string(&file_path)?;
+        let formatted = sqlx_fmt::format_sql(&content, options)?;
+        let is_formatted = content == formatted;
+
+        if !is_formatted {
+            info!("unformatted: {}", file_path.display());
+            unformatted_count += 1;
+        }
+    }
+
     if unformatted_count == 0 {
         info!("all files are formatted correctly");
     } else if fail_on_unformatted {
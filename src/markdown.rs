@@ -0,0 +1,122 @@
+use log::error;
+
+/// Formats the fenced ```rust and ```sql code blocks of a markdown file,
+/// everything else is left untouched
+pub fn format_code_blocks<R, S>(content: &str, mut format_rust: R, mut format_sql: S) -> String
+where
+    R: FnMut(&str) -> String,
+    S: FnMut(&str) -> anyhow::Result<String>,
+{
+    let mut result = String::with_capacity(content.len());
+    let mut lines = content.split_inclusive('\n');
+
+    while let Some(line) = lines.next() {
+        result.push_str(line);
+
+        let Some(fence) = Fence::open(line) else {
+            continue;
+        };
+
+        // collect the code block until the closing fence or the end of the file
+
+        let mut code = String::new();
+        let mut closing = None;
+        for line in lines.by_ref() {
+            if fence.is_closed_by(line) {
+                closing = Some(line);
+                break;
+            }
+            code.push_str(line);
+        }
+
+        let formatted = match fence.language.as_str() {
+            "rust" => format_rust(&code),
+            "sql" if !code.trim().is_empty() => match format_sql(&dedent(&code, fence.indent)) {
+                Ok(sql) => indent(&sql, fence.indent),
+                Err(e) => {
+                    error!("failed to format sql code block: {code:?}, error: {e:?}");
+                    code
+                }
+            },
+            _ => code,
+        };
+
+        result.push_str(&formatted);
+        if let Some(closing) = closing {
+            result.push_str(closing);
+        }
+    }
+
+    result
+}
+
+struct Fence {
+    marker: char,
+    length: usize,
+    indent: usize,
+    /// First word of the info string, e.g. `rust` for ```rust,ignore
+    language: String,
+}
+
+impl Fence {
+    fn open(line: &str) -> Option<Self> {
+        let trimmed = line.trim_start_matches(' ');
+        let indent = line.len() - trimmed.len();
+        let marker = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+        let length = trimmed.chars().take_while(|c| *c == marker).count();
+
+        if indent > 3 || length < 3 {
+            return None;
+        }
+
+        let info = trimmed[length..].trim();
+        if marker == '`' && info.contains('`') {
+            return None;
+        }
+
+        let language = info
+            .split(|c: char| c == ',' || c == '{' || c.is_whitespace())
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+
+        Some(Self {
+            marker,
+            length,
+            indent,
+            language,
+        })
+    }
+
+    fn is_closed_by(&self, line: &str) -> bool {
+        let trimmed = line.trim_start_matches(' ');
+        let length = trimmed.chars().take_while(|c| *c == self.marker).count();
+
+        line.len() - trimmed.len() <= 3
+            && length >= self.length
+            && trimmed[length..].trim().is_empty()
+    }
+}
+
+/// Removes up to `indent` leading spaces of every line, like the indentation of a fence
+fn dedent(code: &str, indent: usize) -> String {
+    code.lines()
+        .map(|line| {
+            let spaces = line.len() - line.trim_start_matches(' ').len();
+            &line[spaces.min(indent)..]
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn indent(code: &str, indent: usize) -> String {
+    code.lines()
+        .map(|line| {
+            if line.trim().is_empty() {
+                "\n".to_string()
+            } else {
+                format!("{}{line}\n", " ".repeat(indent))
+            }
+        })
+        .collect()
+}
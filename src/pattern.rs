@@ -0,0 +1,123 @@
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+
+/// A pattern matching macro or function paths like `sqlx::query`,
+/// either exact, a glob like `*::query*` or a regex like `re:^tenant_.*query$`
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    Exact(String),
+    Glob(String),
+    Regex(Regex),
+}
+
+impl Pattern {
+    pub fn parse(pattern: &str) -> Result<Self> {
+        let pattern = pattern.trim();
+
+        if let Some(regex) = pattern.strip_prefix("re:") {
+            let regex = Regex::new(regex).with_context(|| format!("invalid regex '{regex}'"))?;
+            Ok(Self::Regex(regex))
+        } else if pattern.contains(['*', '?']) {
+            Ok(Self::Glob(pattern.to_string()))
+        } else {
+            Ok(Self::Exact(pattern.to_string()))
+        }
+    }
+
+    pub fn matches(&self, path: &str) -> bool {
+        match self {
+            Self::Exact(exact) => exact == path,
+            Self::Glob(glob) => glob_matches(glob, path),
+            Self::Regex(regex) => regex.is_match(path),
+        }
+    }
+}
+
+/// The argument of a macro or function call that holds the sql
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Argument {
+    /// Position of the argument, starting at 0
+    Position(usize),
+    /// Name of a `name = "..."` argument of a macro
+    Name(String),
+}
+
+/// A pattern with an optional argument holding the sql,
+/// e.g. `cached_query@1` or `audit_query@sql`
+#[derive(Debug, Clone)]
+pub struct Matcher {
+    pub pattern: Pattern,
+    /// Argument holding the sql, all string literals are formatted if `None`
+    pub argument: Option<Argument>,
+}
+
+impl Matcher {
+    pub fn parse(matcher: &str) -> Result<Self> {
+        let matcher = matcher.trim();
+
+        let Some((pattern, argument)) = matcher.rsplit_once('@') else {
+            return Ok(Self {
+                pattern: Pattern::parse(matcher)?,
+                argument: None,
+            });
+        };
+
+        let argument = match argument.trim().parse::<usize>() {
+            Ok(position) => Argument::Position(position),
+            Err(_) if !argument.trim().is_empty() => Argument::Name(argument.trim().to_string()),
+            Err(_) => bail!("missing argument after '@' in '{matcher}'"),
+        };
+
+        Ok(Self {
+            pattern: Pattern::parse(pattern)?,
+            argument: Some(argument),
+        })
+    }
+}
+
+impl From<&str> for Matcher {
+    fn from(exact: &str) -> Self {
+        Self {
+            pattern: exact.into(),
+            argument: None,
+        }
+    }
+}
+
+impl From<&str> for Pattern {
+    fn from(exact: &str) -> Self {
+        Self::Exact(exact.to_string())
+    }
+}
+
+/// Matches `*` against any sequence of characters, including `::`, and `?` against a single one
+fn glob_matches(glob: &str, text: &str) -> bool {
+    let glob: Vec<char> = glob.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut g, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match glob.get(g) {
+            Some('*') => {
+                backtrack = Some((g, t));
+                g += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                g += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    g = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    glob[g..].iter().all(|c| *c == '*')
+}
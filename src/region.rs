@@ -0,0 +1,94 @@
+use crate::sentinel;
+use anyhow::Result;
+use regex::Regex;
+use std::sync::LazyLock;
+
+const OFF_DIRECTIVE: &str = "-- sqlx-fmt: off";
+const ON_DIRECTIVE: &str = "-- sqlx-fmt: on";
+
+static SENTINELS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?imR)^(?<indent>[ \t]*)-- __sqlx_fmt_off_(?<index>\d+)__[ \t]*$")
+        .expect("invalid sentinel regex")
+});
+
+/// Formats sql with `-- sqlx-fmt: off` / `-- sqlx-fmt: on` regions by swapping each region
+/// for a sentinel comment the sql formatter leaves alone, and splicing the region back
+/// verbatim afterwards, indented like the sentinel.
+/// A region without `-- sqlx-fmt: on` runs to the end of the sql.
+pub fn format<F>(sql: &str, mut formatter: F) -> Result<String>
+where
+    F: FnMut(&str) -> Result<String>,
+{
+    let (protected, regions) = protect(sql);
+
+    if regions.is_empty() {
+        return formatter(sql);
+    }
+
+    let formatted = formatter(&protected)?;
+    restore(&formatted, &regions)
+}
+
+fn protect(sql: &str) -> (String, Vec<Vec<&str>>) {
+    let mut protected = Vec::new();
+    let mut regions: Vec<Vec<&str>> = Vec::new();
+    let mut region: Option<Vec<&str>> = None;
+
+    for line in sql.lines() {
+        match region.as_mut() {
+            Some(lines) => {
+                lines.push(line);
+                if is_directive(line, ON_DIRECTIVE) {
+                    regions.extend(region.take());
+                }
+            }
+            None if is_directive(line, OFF_DIRECTIVE) => {
+                protected.push(sentinel(regions.len()));
+                region = Some(vec![line]);
+            }
+            None => protected.push(line.to_string()),
+        }
+    }
+    regions.extend(region);
+
+    (protected.join("\n"), regions)
+}
+
+fn restore(formatted: &str, regions: &[Vec<&str>]) -> Result<String> {
+    sentinel::restore(
+        formatted,
+        &SENTINELS,
+        regions,
+        "sqlx-fmt: off regions",
+        |captures, region| {
+            let indent = &captures["indent"];
+
+            // keep the alignment within the region, but move it to the indentation of the sentinel
+            let region_indent = region
+                .iter()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| line.len() - line.trim_start().len())
+                .min()
+                .unwrap_or(0);
+            region
+                .iter()
+                .map(|line| {
+                    if line.trim().is_empty() {
+                        String::new()
+                    } else {
+                        format!("{indent}{}", &line[region_indent..])
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        },
+    )
+}
+
+fn is_directive(line: &str, directive: &str) -> bool {
+    line.trim().eq_ignore_ascii_case(directive)
+}
+
+fn sentinel(i: usize) -> String {
+    format!("-- {}", sentinel::sentinel("off_", i))
+}
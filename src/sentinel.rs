@@ -0,0 +1,43 @@
+use anyhow::{Result, bail};
+use regex::{Captures, Regex};
+
+/// Sentinel standing in for the `i`th part of sql the formatter has to leave alone,
+/// e.g. `__sqlx_fmt_0__` for a template placeholder or `__sqlx_fmt_off_0__` for an off region
+pub fn sentinel(kind: &str, i: usize) -> String {
+    format!("__sqlx_fmt_{kind}{i}__")
+}
+
+/// Puts `parts` back in place of the sentinels `pattern` matches, its `index` group holding
+/// the number of the sentinel. `pattern` should ignore case, formatters may change the case
+/// of identifiers and comments. Fails unless every part was put back.
+pub fn restore<T>(
+    formatted: &str,
+    pattern: &Regex,
+    parts: &[T],
+    what: &str,
+    mut replace: impl FnMut(&Captures, &T) -> String,
+) -> Result<String> {
+    let mut restored = 0;
+    let result = pattern.replace_all(formatted, |captures: &Captures| {
+        let part = captures["index"]
+            .parse::<usize>()
+            .ok()
+            .and_then(|i| parts.get(i));
+        match part {
+            Some(part) => {
+                restored += 1;
+                replace(captures, part)
+            }
+            None => captures[0].to_string(),
+        }
+    });
+
+    if restored != parts.len() {
+        bail!(
+            "formatter changed the {what}, restored {restored} of {}",
+            parts.len()
+        );
+    }
+
+    Ok(result.into_owned())
+}
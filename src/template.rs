@@ -0,0 +1,66 @@
+use crate::sentinel;
+use anyhow::Result;
+use regex::Regex;
+use std::sync::LazyLock;
+
+static SENTINELS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)__sqlx_fmt_(?<index>\d+)__").expect("invalid sentinel regex")
+});
+
+/// Formats the template of a `format!` like macro, e.g. `select {cols} from {table}`,
+/// by swapping its placeholders and `{{` `}}` escapes for sentinels the sql formatter
+/// leaves alone, and putting them back afterwards
+pub fn format<F>(template: &str, mut formatter: F) -> Result<String>
+where
+    F: FnMut(&str) -> Result<String>,
+{
+    let (sql, placeholders) = protect(template);
+
+    if placeholders.is_empty() {
+        return formatter(template);
+    }
+
+    let formatted = formatter(&sql)?;
+    restore(&formatted, &placeholders)
+}
+
+fn protect(template: &str) -> (String, Vec<String>) {
+    let mut sql = String::with_capacity(template.len());
+    let mut placeholders = Vec::new();
+    let mut chars = template.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let placeholder = match (c, chars.peek()) {
+            ('{', Some((_, '{'))) | ('}', Some((_, '}'))) => {
+                chars.next();
+                &template[i..i + 2]
+            }
+            ('{', _) => match template[i..].find('}') {
+                Some(end) => {
+                    while chars.next_if(|(j, _)| *j <= i + end).is_some() {}
+                    &template[i..=i + end]
+                }
+                None => &template[i..i + 1],
+            },
+            _ => {
+                sql.push(c);
+                continue;
+            }
+        };
+
+        sql.push_str(&sentinel::sentinel("", placeholders.len()));
+        placeholders.push(placeholder.to_string());
+    }
+
+    (sql, placeholders)
+}
+
+fn restore(formatted: &str, placeholders: &[String]) -> Result<String> {
+    sentinel::restore(
+        formatted,
+        &SENTINELS,
+        placeholders,
+        "placeholders of the template",
+        |_, placeholder| placeholder.clone(),
+    )
+}
@@ -1,65 +1,1368 @@
 #![allow(clippy::format_in_format_args)]
 
+use crate::pattern::{Argument, Matcher, Pattern};
+use crate::template;
 use anyhow::{Result, bail};
 use log::{debug, error};
-use tree_sitter::{Node, Parser, Range};
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use tree_sitter::{Node, Parser, Query, QueryCursor, Range, StreamingIterator, Tree};
+use unicode_width::UnicodeWidthChar;
 
-pub fn format_query_macros_literals<F>(
+/// Settings for finding and formatting sql literals
+#[derive(Debug)]
+pub struct Settings {
+    /// Spaces the sql of a multi-line raw string literal is indented by, relative to the literal
+    pub literal_indentation: usize,
+    /// Whether the sql of multi-line raw string literals is indented with spaces or tabs
+    pub indent_style: IndentStyle,
+    /// Columns a tab advances to the next multiple of, for aligning with the code in front of a literal
+    pub tab_width: usize,
+    /// How the sql of multi-line raw string literals is laid out
+    pub layout: Layout,
+    /// Raw string literals whose sql, joined to one line, ends within this display column
+    /// are written on one line, e.g. `r#"select 1"#`, others use the `layout`
+    pub single_line_max_width: Option<usize>,
+    /// Macros whose string literals are formatted, e.g. `sqlx::query`, `*::query*` or `cached_query@1`
+    pub macro_names: Vec<Matcher>,
+    /// Functions whose string literal arguments are formatted, e.g. `sqlx::query_as`
+    pub function_names: Vec<Matcher>,
+    /// Macros whose string literal is a path to a `.sql` file, e.g. `sqlx::query_file`
+    pub query_file_macro_names: Vec<Pattern>,
+    /// Macros whose optional string literal is a migrations directory, e.g. `sqlx::migrate`
+    pub migrate_macro_names: Vec<Pattern>,
+    /// Test attributes whose fixtures are `.sql` files, e.g. `sqlx::test`
+    pub test_attribute_names: Vec<Pattern>,
+    /// Tree-sitter queries whose `@sql` captures are formatted, see [`parse_query`]
+    pub queries: Vec<Query>,
+    /// Also format the rust code blocks of `///` and `//!` doc comments
+    pub doc_comments: bool,
+    /// `format!` like macros whose templates are formatted when passed to one of `function_names`,
+    /// e.g. `format@0`, their `{}` placeholders are left as is
+    pub template_macro_names: Vec<Matcher>,
+    /// How sql spanning multiple lines is written back into normal string literals
+    pub string_style: StringStyle,
+    /// Also format the sql in the bodies of `macro_rules!` definitions
+    pub macro_rules: bool,
+    /// Only format literals overlapping one of these ranges, all literals if empty
+    pub ranges: Vec<SourceRange>,
+}
+
+impl Default for Settings {
+    /// Same indentation and tab width as the default [`crate::Options`]
+    fn default() -> Self {
+        Self {
+            literal_indentation: 4,
+            indent_style: Default::default(),
+            tab_width: 4,
+            layout: Default::default(),
+            single_line_max_width: None,
+            macro_names: Vec::new(),
+            function_names: Vec::new(),
+            query_file_macro_names: Vec::new(),
+            migrate_macro_names: Vec::new(),
+            test_attribute_names: Vec::new(),
+            queries: Vec::new(),
+            doc_comments: false,
+            template_macro_names: Vec::new(),
+            string_style: Default::default(),
+            macro_rules: false,
+            ranges: Vec::new(),
+        }
+    }
+}
+
+/// How sql spanning multiple lines is written back into normal string literals
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StringStyle {
+    /// All lines joined, e.g. `"select * from users where id = $1"`
+    #[default]
+    SingleLine,
+    /// Lines ending with a `\` line continuation, aligned to the literal's opening quote
+    Continuation,
+}
+
+impl FromStr for StringStyle {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "single-line" => Ok(Self::SingleLine),
+            "continuation" => Ok(Self::Continuation),
+            _ => bail!("unknown string style '{s}', expected 'single-line' or 'continuation'"),
+        }
+    }
+}
+
+/// Whitespace the sql of multi-line raw string literals is indented with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IndentStyle {
+    /// `literal_indentation` spaces per level
+    #[default]
+    Spaces,
+    /// A tab per level
+    Tabs,
+}
+
+impl FromStr for IndentStyle {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "spaces" => Ok(Self::Spaces),
+            "tabs" => Ok(Self::Tabs),
+            _ => bail!("unknown indent style '{s}', expected 'spaces' or 'tabs'"),
+        }
+    }
+}
+
+/// Layout of multi-line raw string literals, e.g. for `sqlx::query!(r#"select ..."#)`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Layout {
+    /// The sql indented relative to the opening quote, the closing quote on its own line below the opening quote
+    #[default]
+    Block,
+    /// The sql aligned with the opening quote, the closing quote on its own line below the opening quote
+    Aligned,
+    /// The sql indented relative to the opening quote, the closing quote right after the last line
+    Hanging,
+    /// The sql indented relative to the line of the macro or function call the literal is passed to,
+    /// the closing quote on its own line aligned with that line
+    MacroRelative,
+}
+
+impl FromStr for Layout {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "block" => Ok(Self::Block),
+            "aligned" => Ok(Self::Aligned),
+            "hanging" => Ok(Self::Hanging),
+            "macro-relative" => Ok(Self::MacroRelative),
+            _ => bail!(
+                "unknown layout '{s}', expected 'block', 'aligned', 'hanging' or 'macro-relative'"
+            ),
+        }
+    }
+}
+
+/// Part of a source formatting is restricted to, see [`Settings::ranges`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceRange {
+    /// Lines starting at 1, e.g. `120-180`
+    Lines(RangeInclusive<usize>),
+    /// Byte offsets, e.g. the selection in an editor
+    Bytes(std::ops::Range<usize>),
+}
+
+impl SourceRange {
+    fn overlaps(&self, node: &Node) -> bool {
+        match self {
+            Self::Lines(lines) => {
+                let start = node.start_position().row + 1;
+                let end = node.end_position().row + 1;
+                start <= *lines.end() && *lines.start() <= end
+            }
+            Self::Bytes(bytes) => node.start_byte() < bytes.end && bytes.start < node.end_byte(),
+        }
+    }
+}
+
+impl FromStr for SourceRange {
+    type Err = anyhow::Error;
+
+    /// Parses lines like `120-180` or a single line like `120`
+    fn from_str(s: &str) -> Result<Self> {
+        let (start, end) = s.split_once('-').unwrap_or((s, s));
+        let (Ok(start), Ok(end)) = (start.trim().parse::<usize>(), end.trim().parse::<usize>())
+        else {
+            bail!("invalid line range '{s}', expected lines like '120-180' or '120'");
+        };
+        if start == 0 || start > end {
+            bail!(
+                "invalid line range '{s}', lines start at 1 and the end must not be before the start"
+            );
+        }
+
+        Ok(Self::Lines(start..=end))
+    }
+}
+
+/// Sql living outside of a rust file that is referenced from it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SqlReference {
+    /// Path passed to `query_file!` and friends, relative to the crate's manifest dir
+    QueryFile(String),
+    /// Directory passed to `migrate!`, relative to the crate's manifest dir,
+    /// `None` if the default `./migrations` is used
+    Migrations(Option<String>),
+    /// Fixture of a test attribute like `#[sqlx::test(fixtures("users"))]`,
+    /// resolved to a path relative to the rust file, e.g. `./fixtures/users.sql`
+    Fixture(String),
+}
+
+/// A source with its sql literals formatted
+#[derive(Debug)]
+pub struct Formatted {
+    pub source: String,
+    /// Byte ranges of the original source that were replaced and the length of their replacement, in order
+    pub replaced: Vec<(std::ops::Range<usize>, usize)>,
+    /// Sql literals left as is because they are in or next to a syntax error
+    pub skipped: Vec<Range>,
+}
+
+impl Formatted {
+    /// Byte offset in the original source of `offset` in the formatted one,
+    /// `None` if it lies within a replacement
+    pub fn original_offset(&self, offset: usize) -> Option<usize> {
+        let mut shift: isize = 0;
+        for (range, len) in &self.replaced {
+            let start = range.start.checked_add_signed(shift)?;
+            if offset < start {
+                break;
+            }
+            if offset < start + len {
+                return None;
+            }
+            shift += *len as isize - range.len() as isize;
+        }
+
+        offset.checked_add_signed(-shift)
+    }
+}
+
+pub fn format_query_macros_literals<F>(source: &str, settings: &Settings, formatter: F) -> String
+where
+    F: FnMut(&str, bool) -> Result<String>,
+{
+    format_query_macros(source, settings, formatter).source
+}
+
+/// Like `format_query_macros_literals`, but also tells which parts of `source`
+/// were replaced and which sql was left as is
+pub fn format_query_macros<F>(source: &str, settings: &Settings, formatter: F) -> Formatted
+where
+    F: FnMut(&str, bool) -> Result<String>,
+{
+    format_literals(source, source, settings, formatter)
+}
+
+/// Formats a rust code block of a markdown file, e.g. of an mdBook chapter,
+/// whose hidden lines like `# use sqlx::PgPool;` are parsed as code
+pub fn format_code_block_literals<F>(code: &str, settings: &Settings, formatter: F) -> Formatted
+where
+    F: FnMut(&str, bool) -> Result<String>,
+{
+    let parsed: String = code
+        .split_inclusive('\n')
+        .map(|line| match hidden_line_hash(line) {
+            Some(hash) => format!("{} {}", &line[..hash], &line[hash + 1..]),
+            None => line.to_string(),
+        })
+        .collect();
+
+    format_literals(code, &parsed, settings, formatter)
+}
+
+/// Formats the sql literals of `source` found in `parsed`, a copy of `source`
+/// with the same byte offsets that is parsed in its place
+fn format_literals<F>(
     source: &str,
-    literal_indentation: usize,
-    macros_names: Vec<String>,
+    parsed: &str,
+    settings: &Settings,
     mut formatter: F,
-) -> String
+) -> Formatted
+where
+    F: FnMut(&str, bool) -> Result<String>,
+{
+    if source.lines().any(|line| {
+        line.trim()
+            .strip_prefix("//!")
+            .is_some_and(|comment| comment.trim() == SKIP_FILE_DIRECTIVE)
+    }) {
+        return Formatted {
+            source: source.to_string(),
+            replaced: Vec::new(),
+            skipped: Vec::new(),
+        };
+    }
+
+    let (mut replacements, mut skipped) = collect_replacements(parsed, settings, &mut formatter);
+
+    // rustdoc examples are formatted with the comment markers blanked out,
+    // which keeps byte offsets and columns the same as in the source
+
+    if settings.doc_comments
+        && let Some(doc_code) = DocCode::extract(parsed)
+    {
+        let (doc_replacements, doc_skipped) =
+            collect_replacements(&doc_code.source, settings, &mut formatter);
+        for (range, replacement) in doc_replacements {
+            let replacement = doc_code.prefix_lines(range.start_point.row, &replacement);
+            replacements.push((range, replacement));
+        }
+        skipped.extend(doc_skipped);
+    }
+
+    // repace unformatted with formatted sql
+
+    replacements.sort_by_key(|(range, _)| range.start_byte);
+    skipped.sort_by_key(|range| range.start_byte);
+
+    let mut result = source.to_string();
+    for (range, replacement) in replacements.iter().rev() {
+        let start = range.start_byte;
+        let end = range.end_byte;
+        result.replace_range(start..end, replacement);
+    }
+
+    Formatted {
+        source: result,
+        replaced: replacements
+            .iter()
+            .map(|(range, replacement)| (range.start_byte..range.end_byte, replacement.len()))
+            .collect(),
+        skipped,
+    }
+}
+
+/// Collects the formatted sql literals of `source` and the ones left as is next to syntax errors
+fn collect_replacements<F>(
+    source: &str,
+    settings: &Settings,
+    formatter: &mut F,
+) -> (Vec<(Range, String)>, Vec<Range>)
 where
     F: FnMut(&str, bool) -> Result<String>,
 {
-    // setup rust parser
+    // setup rust parser
+
+    let mut parser = rust_parser();
+    let tree = parser
+        .parse(source.as_bytes(), None)
+        .expect("Failed to parse code");
+    let root_node = tree.root_node();
+
+    // find and collect replacements
+
+    let mut replacements: Vec<(Range, String)> = Vec::new();
+    let aliases = Aliases::collect(root_node, source.as_bytes());
+    let skips = Skips::collect(root_node, source.as_bytes());
+
+    find_and_collect(
+        root_node,
+        source.as_bytes(),
+        settings,
+        &aliases,
+        &skips,
+        formatter,
+        &mut replacements,
+    );
+    collect_query_captures(
+        root_node,
+        source.as_bytes(),
+        settings,
+        &skips,
+        formatter,
+        &mut replacements,
+    );
+    collect_marked_literals(
+        root_node,
+        source.as_bytes(),
+        settings,
+        &skips,
+        formatter,
+        &mut replacements,
+    );
+
+    // the tree in and around syntax errors, e.g. of a half-edited file,
+    // might not match the source, so sql next to them is left as is
+
+    let mut errors = Vec::new();
+    collect_syntax_errors(root_node, &mut errors);
+    let (skipped, replacements): (Vec<_>, Vec<_>) =
+        replacements.into_iter().partition(|(range, _)| {
+            errors.iter().any(|error| {
+                range.start_point.row <= error.end_point.row + 1
+                    && error.start_point.row <= range.end_point.row + 1
+            })
+        });
+    let skipped = skipped.into_iter().map(|(range, _)| range).collect();
+
+    (replacements, skipped)
+}
+
+/// Ranges of the `ERROR` and `MISSING` nodes of a rust source,
+/// sql in or next to them is not formatted
+pub fn syntax_errors(source: &str) -> Vec<Range> {
+    let mut parser = rust_parser();
+    let tree = parser
+        .parse(source.as_bytes(), None)
+        .expect("Failed to parse code");
+
+    let mut errors = Vec::new();
+    collect_syntax_errors(tree.root_node(), &mut errors);
+    errors
+}
+
+fn collect_syntax_errors(node: Node, errors: &mut Vec<Range>) {
+    if !node.has_error() {
+        return;
+    }
+    if node.is_error() || node.is_missing() {
+        errors.push(node.range());
+        return;
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_syntax_errors(child, errors);
+    }
+}
+
+const SKIP_DIRECTIVE: &str = "sqlx-fmt: skip";
+const SKIP_FILE_DIRECTIVE: &str = "sqlx-fmt: skip-file";
+const SKIP_ATTRIBUTES: [&str; 2] = ["sqlx_fmt::skip", "rustfmt::skip"];
+
+/// Byte ranges excluded from formatting by a `// sqlx-fmt: skip` comment
+/// or a `#[sqlx_fmt::skip]` or `#[rustfmt::skip]` attribute
+#[derive(Debug, Clone, Default)]
+struct Skips(Vec<std::ops::Range<usize>>);
+
+impl Skips {
+    fn collect(root: Node, source: &[u8]) -> Self {
+        let mut skips = Self::default();
+        skips.collect_in(root, source);
+        skips
+    }
+
+    fn collect_in(&mut self, node: Node, source: &[u8]) {
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.children(&mut cursor).collect();
+
+        for (i, child) in children.iter().enumerate() {
+            let target = match child.kind() {
+                "line_comment" | "block_comment"
+                    if child.child_by_field_name("doc").is_none()
+                        && child
+                            .utf8_text(source)
+                            .is_ok_and(|comment| comment_text(comment) == SKIP_DIRECTIVE) =>
+                {
+                    // a trailing comment applies to the code on its line,
+                    // otherwise to the code following it
+                    match i.checked_sub(1).map(|i| children[i]) {
+                        Some(previous)
+                            if previous.end_position().row == child.start_position().row =>
+                        {
+                            Some(previous)
+                        }
+                        _ => next_code_sibling(&children[i + 1..]),
+                    }
+                }
+                "attribute_item" if is_skip_attribute(child, source) => {
+                    next_code_sibling(&children[i + 1..])
+                }
+                "inner_attribute_item" if is_skip_attribute(child, source) => Some(node),
+                _ => {
+                    self.collect_in(*child, source);
+                    None
+                }
+            };
+
+            if let Some(target) = target {
+                self.0.push(target.byte_range());
+            }
+        }
+    }
+
+    fn contains(&self, node: &Node) -> bool {
+        self.0
+            .iter()
+            .any(|range| range.start <= node.start_byte() && node.end_byte() <= range.end)
+    }
+}
+
+/// Returns the first node that is neither a comment nor an attribute
+fn next_code_sibling<'t>(siblings: &[Node<'t>]) -> Option<Node<'t>> {
+    siblings
+        .iter()
+        .find(|sibling| {
+            !matches!(
+                sibling.kind(),
+                "line_comment" | "block_comment" | "attribute_item"
+            )
+        })
+        .copied()
+}
+
+fn is_skip_attribute(attribute_item: &Node, source: &[u8]) -> bool {
+    let mut cursor = attribute_item.walk();
+    attribute_item
+        .children(&mut cursor)
+        .find(|n| n.kind() == "attribute")
+        .and_then(|attribute| attribute.utf8_text(source).ok())
+        .is_some_and(|attribute| {
+            let attribute = attribute.trim().trim_start_matches("::");
+            SKIP_ATTRIBUTES.contains(&attribute)
+        })
+}
+
+/// The rust code blocks of the `///` and `//!` doc comments of a file,
+/// with everything else blanked out
+struct DocCode {
+    source: String,
+    /// Indentation and comment marker of each code line by row, e.g. `    ///`
+    prefixes: HashMap<usize, String>,
+}
+
+impl DocCode {
+    fn extract(source: &str) -> Option<Self> {
+        let mut parser = rust_parser();
+        let tree = parser.parse(source.as_bytes(), None)?;
+
+        let mut doc_lines = Vec::new();
+        collect_doc_lines(tree.root_node(), &mut doc_lines);
+
+        let mut blanked: Vec<u8> = source
+            .bytes()
+            .map(|b| if b == b'\n' || b == b'\r' { b } else { b' ' })
+            .collect();
+        let mut prefixes = HashMap::new();
+
+        // `Some(true)` inside of a rust code block
+        let mut fence: Option<bool> = None;
+        let mut previous: Option<(usize, &str)> = None;
+
+        for (comment, doc) in doc_lines {
+            let row = comment.start_position().row;
+            let marker = &source[comment.start_byte()..comment.start_byte() + 3];
+
+            // a fence ends with its doc comment block
+
+            if previous.is_some_and(|(previous_row, previous_marker)| {
+                previous_row + 1 != row || previous_marker != marker
+            }) {
+                fence = None;
+            }
+            previous = Some((row, marker));
+
+            let content = &source[doc.start_byte()..doc.end_byte()];
+            let trimmed = content.trim();
+
+            if let Some(info) = trimmed.strip_prefix("```") {
+                fence = match fence {
+                    None => Some(is_rust_fence(info)),
+                    Some(_) => None,
+                };
+                continue;
+            }
+
+            if fence != Some(true) {
+                continue;
+            }
+
+            for (i, b) in content.bytes().enumerate() {
+                if b != b'\n' && b != b'\r' {
+                    blanked[doc.start_byte() + i] = b;
+                }
+            }
+
+            // hidden lines like `# use sqlx::PgPool;` are still code
+
+            if let Some(hash) = hidden_line_hash(content) {
+                blanked[doc.start_byte() + hash] = b' ';
+            }
+
+            let line_start = source[..comment.start_byte()]
+                .rfind('\n')
+                .map(|i| i + 1)
+                .unwrap_or(0);
+            prefixes.insert(row, source[line_start..doc.start_byte()].to_string());
+        }
+
+        if prefixes.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            source: String::from_utf8(blanked).ok()?,
+            prefixes,
+        })
+    }
+
+    /// Puts the comment marker of `row` in front of every line of a multi-line replacement
+    fn prefix_lines(&self, row: usize, replacement: &str) -> String {
+        let Some(prefix) = self.prefixes.get(&row) else {
+            return replacement.to_string();
+        };
+
+        let mut lines = replacement.split('\n');
+        let mut prefixed = lines.next().unwrap_or_default().to_string();
+
+        for line in lines {
+            prefixed.push('\n');
+            match line.get(prefix.len()..) {
+                Some(rest) if line[..prefix.len()].trim().is_empty() => {
+                    prefixed.push_str(prefix);
+                    prefixed.push_str(rest);
+                }
+                _ if line.trim().is_empty() => prefixed.push_str(prefix.trim_end()),
+                _ => {
+                    prefixed.push_str(prefix);
+                    prefixed.push_str(line.trim_start());
+                }
+            }
+        }
+
+        prefixed
+    }
+}
+
+/// Collects `///` and `//!` comments with their `doc_comment` content node
+fn collect_doc_lines<'t>(node: Node<'t>, doc_lines: &mut Vec<(Node<'t>, Node<'t>)>) {
+    let mut cursor = node.walk();
+
+    for child in node.children(&mut cursor) {
+        if child.kind() == "line_comment"
+            && let Some(doc) = child.child_by_field_name("doc")
+        {
+            doc_lines.push((child, doc));
+        }
+        collect_doc_lines(child, doc_lines);
+    }
+}
+
+/// Byte offset of the `#` hiding a line of a rustdoc or mdBook example, e.g. `# use sqlx::PgPool;`
+fn hidden_line_hash(line: &str) -> Option<usize> {
+    let trimmed = line.trim();
+    if trimmed == "#" || trimmed.starts_with("# ") {
+        line.find('#')
+    } else {
+        None
+    }
+}
+
+/// Code blocks without a language or with rustdoc attributes only are rust code
+fn is_rust_fence(info: &str) -> bool {
+    info.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|attribute| !attribute.is_empty())
+        .all(|attribute| {
+            matches!(
+                attribute,
+                "rust"
+                    | "ignore"
+                    | "no_run"
+                    | "should_panic"
+                    | "compile_fail"
+                    | "test_harness"
+                    | "standalone_crate"
+            ) || attribute.starts_with("edition")
+        })
+}
+
+/// Parses a tree-sitter query for rust, the literals captured as `@sql` are formatted, e.g.
+/// `(call_expression function: (field_expression field: (field_identifier) @f (#eq? @f "execute")) arguments: (arguments (string_literal) @sql))`
+pub fn parse_query(query: &str) -> Result<Query> {
+    let language: tree_sitter::Language = tree_sitter_rust::LANGUAGE.into();
+    let query = Query::new(&language, query)?;
+
+    if query.capture_index_for_name(SQL_CAPTURE).is_none() {
+        bail!("query has no @{SQL_CAPTURE} capture");
+    }
+
+    Ok(query)
+}
+
+const SQL_CAPTURE: &str = "sql";
+
+fn rust_parser() -> Parser {
+    let language: tree_sitter::Language = tree_sitter_rust::LANGUAGE.into();
+    let mut parser = Parser::new();
+    parser
+        .set_language(&language)
+        .expect("Error loading Rust grammar");
+    parser
+}
+
+fn find_and_collect<F>(
+    node: Node,
+    source: &[u8],
+    settings: &Settings,
+    aliases: &Aliases,
+    skips: &Skips,
+    formatter: &mut F,
+    replacements: &mut Vec<(Range, String)>,
+) where
+    F: FnMut(&str, bool) -> Result<String>,
+{
+    let mut cursor = node.walk();
+
+    for child in node.children(&mut cursor) {
+        if skips.contains(&child) {
+            continue;
+        }
+
+        if child.kind() == "macro_invocation"
+            && let Some(macro_node) = child.child_by_field_name("macro")
+        {
+            let macro_name = macro_node
+                .utf8_text(source)
+                .expect("failed to get macro name as utf8");
+            if matches(&settings.query_file_macro_names, macro_name, aliases)
+                || matches(&settings.migrate_macro_names, macro_name, aliases)
+            {
+                // the path literals of `query_file!` and `migrate!` are not sql,
+                // even if a macro pattern like `*::query*` matches them
+                debug!("skipping path literal of {macro_name}");
+            } else if let Some(matcher) = find_matcher(&settings.macro_names, macro_name, aliases) {
+                let mut cursor = child.walk();
+                match &matcher.argument {
+                    None => {
+                        for macro_child in child.children(&mut cursor) {
+                            collect_literals(
+                                macro_child,
+                                source,
+                                settings,
+                                formatter,
+                                replacements,
+                            );
+                        }
+                    }
+                    Some(argument) => {
+                        if let Some(token_tree) = child
+                            .children(&mut cursor)
+                            .find(|n| n.kind() == "token_tree")
+                            && let Some(literal) = macro_argument(&token_tree, argument, source)
+                        {
+                            collect_literal(literal, source, settings, formatter, replacements);
+                        }
+                    }
+                }
+            } else {
+                // tree-sitter leaves the arguments of other macros like `tokio::try_join!`
+                // or `vec!` as an unparsed token tree, so we re-parse it as an expression
+                // to find sqlx macros nested inside of it
+
+                let mut cursor = child.walk();
+                if let Some(token_tree) = child
+                    .children(&mut cursor)
+                    .find(|n| n.kind() == "token_tree")
+                {
+                    collect_in_token_tree(
+                        token_tree,
+                        source,
+                        settings,
+                        aliases,
+                        skips,
+                        formatter,
+                        replacements,
+                    );
+                }
+            }
+        } else if settings.macro_rules
+            && child.kind() == "macro_rule"
+            && let Some(body) = child.child_by_field_name("right")
+        {
+            // tree-sitter-rust parses `$ident` metavariables in expressions, types and patterns,
+            // so the sql literals of a macro body are found like in normal code while the
+            // metavariables around them are left as is
+
+            collect_in_token_tree(
+                body,
+                source,
+                settings,
+                aliases,
+                skips,
+                formatter,
+                replacements,
+            );
+            continue;
+        } else if child.kind() == "call_expression"
+            && let Some(function_name) = call_function_name(&child, source)
+            && let Some(matcher) = find_matcher(&settings.function_names, &function_name, aliases)
+            && let Some(arguments) = child.child_by_field_name("arguments")
+        {
+            match &matcher.argument {
+                None => collect_literals(arguments, source, settings, formatter, replacements),
+                Some(argument) => {
+                    if let Some(literal) = function_argument(&arguments, argument) {
+                        collect_literal(literal, source, settings, formatter, replacements);
+                    }
+                }
+            }
+            collect_templates(
+                arguments,
+                source,
+                settings,
+                aliases,
+                formatter,
+                replacements,
+            );
+        }
+        find_and_collect(
+            child,
+            source,
+            settings,
+            aliases,
+            skips,
+            formatter,
+            replacements,
+        );
+    }
+}
+
+/// Re-parses a token tree as rust code and collects the sql literals inside of it
+fn collect_in_token_tree<F>(
+    token_tree: Node,
+    source: &[u8],
+    settings: &Settings,
+    aliases: &Aliases,
+    skips: &Skips,
+    formatter: &mut F,
+    replacements: &mut Vec<(Range, String)>,
+) where
+    F: FnMut(&str, bool) -> Result<String>,
+{
+    let Some(tree) = parse_token_tree(token_tree, source) else {
+        return;
+    };
+
+    // skip directives inside the token tree only show up after re-parsing
+    let mut skips = skips.clone();
+    skips.collect_in(tree.root_node(), source);
+
+    find_and_collect(
+        tree.root_node(),
+        source,
+        settings,
+        aliases,
+        &skips,
+        formatter,
+        replacements,
+    );
+    collect_query_captures(
+        tree.root_node(),
+        source,
+        settings,
+        &skips,
+        formatter,
+        replacements,
+    );
+}
+
+/// Checks if `path` as written, or the path it resolves to, matches one of `patterns`
+fn matches(patterns: &[Pattern], path: &str, aliases: &Aliases) -> bool {
+    let path = path.trim_start_matches("::");
+    let resolved = aliases.resolve(path);
+    patterns
+        .iter()
+        .any(|pattern| pattern.matches(path) || pattern.matches(&resolved))
+}
+
+/// Collects the templates of `format!` like macros in the arguments of a sqlx function,
+/// e.g. `sqlx::query(&format!("select {cols} from users"))`
+fn collect_templates<F>(
+    node: Node,
+    source: &[u8],
+    settings: &Settings,
+    aliases: &Aliases,
+    formatter: &mut F,
+    replacements: &mut Vec<(Range, String)>,
+) where
+    F: FnMut(&str, bool) -> Result<String>,
+{
+    let mut cursor = node.walk();
+
+    for child in node.children(&mut cursor) {
+        if child.kind() == "macro_invocation"
+            && let Some(macro_node) = child.child_by_field_name("macro")
+            && let Ok(macro_name) = macro_node.utf8_text(source)
+            && let Some(matcher) = find_matcher(&settings.template_macro_names, macro_name, aliases)
+        {
+            let argument = matcher.argument.clone().unwrap_or(Argument::Position(0));
+            let mut cursor = child.walk();
+            if let Some(token_tree) = child
+                .children(&mut cursor)
+                .find(|n| n.kind() == "token_tree")
+                && let Some(literal) = macro_argument(&token_tree, &argument, source)
+            {
+                let mut template_formatter =
+                    |sql: &str, is_raw: bool| template::format(sql, |sql| formatter(sql, is_raw));
+                collect_literal(
+                    literal,
+                    source,
+                    settings,
+                    &mut template_formatter,
+                    replacements,
+                );
+            }
+            continue;
+        }
+        collect_templates(child, source, settings, aliases, formatter, replacements);
+    }
+}
+
+/// Returns the first of `matchers` matching `path` as written or the path it resolves to
+fn find_matcher<'m>(matchers: &'m [Matcher], path: &str, aliases: &Aliases) -> Option<&'m Matcher> {
+    let path = path.trim_start_matches("::");
+    let resolved = aliases.resolve(path);
+    matchers
+        .iter()
+        .find(|matcher| matcher.pattern.matches(path) || matcher.pattern.matches(&resolved))
+}
+
+/// Returns the literal of a positional or `name = "..."` argument in the token tree of a macro
+fn macro_argument<'t>(
+    token_tree: &Node<'t>,
+    argument: &Argument,
+    source: &[u8],
+) -> Option<Node<'t>> {
+    let mut cursor = token_tree.walk();
+    let tokens: Vec<Node> = token_tree.children(&mut cursor).collect();
+
+    // skip the delimiters and split at top level commas, commas in nested
+    // token trees are not direct children
+
+    let inner = tokens.get(1..tokens.len().saturating_sub(1))?;
+    let mut arguments = inner.split(|n| n.kind() == ",");
+
+    let tokens = match argument {
+        Argument::Position(position) => arguments.nth(*position)?,
+        Argument::Name(name) => {
+            let argument = arguments.find(|tokens| {
+                matches!(tokens, [ident, eq, ..] if ident.utf8_text(source) == Ok(name) && eq.kind() == "=")
+            })?;
+            &argument[2..]
+        }
+    };
+
+    match tokens {
+        [literal] if matches!(literal.kind(), "string_literal" | "raw_string_literal") => {
+            Some(*literal)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the literal of a positional argument of a function call
+fn function_argument<'t>(arguments: &Node<'t>, argument: &Argument) -> Option<Node<'t>> {
+    let Argument::Position(position) = argument else {
+        debug!("functions only have positional arguments, skipping {argument:?}");
+        return None;
+    };
+
+    let mut cursor = arguments.walk();
+    arguments
+        .named_children(&mut cursor)
+        .filter(|n| !n.is_extra())
+        .nth(*position)
+        .filter(|n| matches!(n.kind(), "string_literal" | "raw_string_literal"))
+}
+
+/// Paths brought into scope by the `use` declarations of a file,
+/// e.g. `qa` => `sqlx::query_as` for `use sqlx::query_as as qa;`.
+/// Scopes are not tracked, items used in inline modules are also
+/// reachable through the module path, e.g. `db::qa`.
+#[derive(Debug, Default)]
+struct Aliases(HashMap<String, String>);
+
+impl Aliases {
+    fn collect(root: Node, source: &[u8]) -> Self {
+        let mut aliases = Self::default();
+        aliases.collect_in(root, source, "");
+        aliases
+    }
+
+    fn collect_in(&mut self, node: Node, source: &[u8], module: &str) {
+        let mut cursor = node.walk();
+
+        for child in node.children(&mut cursor) {
+            match child.kind() {
+                "use_declaration" => {
+                    if let Some(argument) = child.child_by_field_name("argument") {
+                        self.collect_use(argument, source, module, "");
+                    }
+                }
+                "mod_item" => {
+                    if let Some(name) = child.child_by_field_name("name")
+                        && let Ok(name) = name.utf8_text(source)
+                        && let Some(body) = child.child_by_field_name("body")
+                    {
+                        self.collect_in(body, source, &join_path(module, name));
+                    }
+                }
+                _ => self.collect_in(child, source, module),
+            }
+        }
+    }
+
+    fn collect_use(&mut self, node: Node, source: &[u8], module: &str, prefix: &str) {
+        let text = |n: Node| n.utf8_text(source).unwrap_or_default();
+
+        match node.kind() {
+            "identifier" | "scoped_identifier" => {
+                let path = join_path(prefix, text(node));
+                let name = node
+                    .child_by_field_name("name")
+                    .map(text)
+                    .unwrap_or(text(node));
+                self.insert(module, name, path);
+            }
+            "self" => {
+                if let Some(name) = prefix.rsplit("::").next() {
+                    self.insert(module, name, prefix.to_string());
+                }
+            }
+            "use_as_clause" => {
+                if let Some(path) = node.child_by_field_name("path")
+                    && let Some(alias) = node.child_by_field_name("alias")
+                {
+                    let path = if path.kind() == "self" {
+                        prefix.to_string()
+                    } else {
+                        join_path(prefix, text(path))
+                    };
+                    self.insert(module, text(alias), path);
+                }
+            }
+            "scoped_use_list" => {
+                let prefix = match node.child_by_field_name("path") {
+                    Some(path) => join_path(prefix, text(path)),
+                    None => prefix.to_string(),
+                };
+                if let Some(list) = node.child_by_field_name("list") {
+                    self.collect_use(list, source, module, &prefix);
+                }
+            }
+            "use_list" => {
+                let mut cursor = node.walk();
+                for item in node.named_children(&mut cursor) {
+                    self.collect_use(item, source, module, prefix);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn insert(&mut self, module: &str, name: &str, path: String) {
+        let path = path.trim_start_matches("::").to_string();
+        if name == path {
+            return;
+        }
+        if !module.is_empty() {
+            self.0.insert(join_path(module, name), path.clone());
+        }
+        self.0.insert(name.to_string(), path);
+    }
+
+    /// Resolves the longest aliased prefix of `path`, e.g. `qa` => `sqlx::query_as`
+    fn resolve(&self, path: &str) -> String {
+        let mut path = path.trim_start_matches("::").to_string();
+
+        // aliases can point to other aliases, e.g. `use sqlx as s; use s::query as q;`
+        for _ in 0..8 {
+            for prefix in ["crate::", "self::"] {
+                if let Some(stripped) = path.strip_prefix(prefix) {
+                    path = stripped.to_string();
+                }
+            }
+
+            let segments: Vec<&str> = path.split("::").collect();
+            let resolved = (1..=segments.len()).rev().find_map(|i| {
+                let alias = self.0.get(&segments[..i].join("::"))?;
+                Some(join_path(alias, &segments[i..].join("::")))
+            });
+
+            match resolved {
+                Some(resolved) if resolved != path => path = resolved,
+                _ => break,
+            }
+        }
+
+        path
+    }
+}
+
+fn join_path(prefix: &str, path: &str) -> String {
+    let path = path.trim_start_matches("::");
+    if prefix.is_empty() {
+        path.to_string()
+    } else if path.is_empty() {
+        prefix.to_string()
+    } else {
+        format!("{prefix}::{path}")
+    }
+}
+
+/// Returns the path of the called function, without generic arguments,
+/// e.g. `sqlx::query_as` for `sqlx::query_as::<_, User>("...")`,
+/// or the name of a called method prefixed with a dot, e.g. `.execute` for `client.execute("...")`
+fn call_function_name(call_expression: &Node, source: &[u8]) -> Option<String> {
+    let mut function = call_expression.child_by_field_name("function")?;
+    if function.kind() == "generic_function" {
+        function = function.child_by_field_name("function")?;
+    }
+
+    match function.kind() {
+        "identifier" | "scoped_identifier" => function.utf8_text(source).ok().map(String::from),
+        "field_expression" => {
+            let method = function
+                .child_by_field_name("field")?
+                .utf8_text(source)
+                .ok()?;
+            Some(format!(".{method}"))
+        }
+        _ => None,
+    }
+}
+
+fn collect_literals<F>(
+    node: Node,
+    source: &[u8],
+    settings: &Settings,
+    formatter: &mut F,
+    replacements: &mut Vec<(Range, String)>,
+) where
+    F: FnMut(&str, bool) -> Result<String>,
+{
+    let cursor = &mut node.walk();
+    for kind in ["raw_string_literal", "string_literal"] {
+        if let Some(literal) = node.children(cursor).find(|n| n.kind() == kind) {
+            collect_literal(literal, source, settings, formatter, replacements);
+        }
+    }
+}
 
-    let language: tree_sitter::Language = tree_sitter_rust::LANGUAGE.into();
-    let mut parser = Parser::new();
-    parser
-        .set_language(&language)
-        .expect("Error loading Rust grammar");
-    let tree = parser
-        .parse(source.as_bytes(), None)
-        .expect("Failed to parse code");
-    let root_node = tree.root_node();
+fn collect_literal<F>(
+    literal: Node,
+    source: &[u8],
+    settings: &Settings,
+    formatter: &mut F,
+    replacements: &mut Vec<(Range, String)>,
+) where
+    F: FnMut(&str, bool) -> Result<String>,
+{
+    if !settings.ranges.is_empty() && !settings.ranges.iter().any(|range| range.overlaps(&literal))
+    {
+        debug!("skipping literal outside of the ranges to format");
+        return;
+    }
 
-    // find and collect replacements
+    // byte and C strings like `b"..."` or `cr#"..."#` would lose their prefix
+    if matches!(source.get(literal.start_byte()), Some(b'b' | b'c')) {
+        debug!("skipping byte or C string literal, only str literals are formatted");
+        return;
+    }
 
-    let mut replacements: Vec<(Range, String)> = Vec::new();
+    match literal.kind() {
+        // raw string literal
+        "raw_string_literal" => {
+            match format_raw_string_literal(source, &literal, settings, formatter) {
+                Ok(v) => replacements.push((literal.range(), v)),
+                Err(e) => {
+                    error!(
+                        "failed to format raw string literal: {:?}, error: {:?}",
+                        literal.utf8_text(source),
+                        e
+                    );
+                }
+            }
+        }
+        // string literal
+        "string_literal" => match format_string_literal(source, &literal, settings, formatter) {
+            Ok(v) => replacements.push((literal.range(), v)),
+            Err(e) => {
+                error!(
+                    "failed to format string literal: {:?}, error: {:?}",
+                    literal.utf8_text(source),
+                    e
+                );
+            }
+        },
+        kind => debug!("skipping {kind} node, only string literals are formatted"),
+    }
+}
 
-    find_and_collect(
-        root_node,
-        source.as_bytes(),
-        &macros_names,
-        literal_indentation,
-        &mut formatter,
-        &mut replacements,
-    );
+/// Collects literals marked with a `/* sql */` or `// language=sql` comment, wherever they are,
+/// e.g. `const FIND_USER: &str = /* sql */ r#"select ..."#;`
+fn collect_marked_literals<F>(
+    root: Node,
+    source: &[u8],
+    settings: &Settings,
+    skips: &Skips,
+    formatter: &mut F,
+    replacements: &mut Vec<(Range, String)>,
+) where
+    F: FnMut(&str, bool) -> Result<String>,
+{
+    let mut markers = Vec::new();
+    let mut literals = Vec::new();
+    collect_markers_and_literals(root, source, &mut markers, &mut literals);
 
-    // repace unformatted with formatted sql
+    for marker in markers {
+        // the marker applies to the next literal of the same statement
+        let Some(literal) = literals
+            .iter()
+            .find(|literal| literal.start_byte() >= marker.end_byte())
+        else {
+            continue;
+        };
+        let between = &source[marker.end_byte()..literal.start_byte()];
+        if between.iter().any(|b| matches!(b, b';' | b'{' | b'}')) {
+            continue;
+        }
 
-    let mut result = source.to_string();
-    for (range, replacement) in replacements.into_iter().rev() {
-        let start = range.start_byte;
-        let end = range.end_byte;
-        result.replace_range(start..end, &replacement);
+        if skips.contains(literal)
+            || replacements
+                .iter()
+                .any(|(range, _)| range.start_byte == literal.start_byte())
+        {
+            continue;
+        }
+        collect_literal(*literal, source, settings, formatter, replacements);
+    }
+}
+
+fn collect_markers_and_literals<'t>(
+    node: Node<'t>,
+    source: &[u8],
+    markers: &mut Vec<Node<'t>>,
+    literals: &mut Vec<Node<'t>>,
+) {
+    let mut cursor = node.walk();
+
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "line_comment" | "block_comment" => {
+                if child.child_by_field_name("doc").is_none()
+                    && let Ok(comment) = child.utf8_text(source)
+                    && is_sql_marker(comment)
+                {
+                    markers.push(child);
+                }
+            }
+            "string_literal" | "raw_string_literal" => literals.push(child),
+            _ => collect_markers_and_literals(child, source, markers, literals),
+        }
+    }
+}
+
+/// Checks for `/* sql */` and `// language=sql` like comments, the language can
+/// be any sql dialect like `// language=PostgreSQL`.
+/// A bare `sql` only counts in block comments, as `// SQL` is a common plain comment.
+fn is_sql_marker(comment: &str) -> bool {
+    let is_block_comment = comment.trim_start().starts_with("/*");
+    let comment = comment_text(comment).to_lowercase();
+
+    match comment.strip_prefix("language") {
+        Some(language) => language
+            .trim_start()
+            .strip_prefix('=')
+            .is_some_and(|language| language.trim().contains("sql")),
+        None => is_block_comment && comment == "sql",
     }
+}
 
-    result
+/// Returns the text of a line or block comment without the comment markers
+fn comment_text(comment: &str) -> &str {
+    comment
+        .trim()
+        .trim_start_matches("//")
+        .trim_start_matches("/*")
+        .trim_end_matches("*/")
+        .trim()
 }
 
-fn find_and_collect<'a, F>(
-    node: Node<'a>,
-    source: &'a [u8],
-    macro_names: &Vec<String>,
-    _literal_indentation: usize,
+fn collect_query_captures<F>(
+    node: Node,
+    source: &[u8],
+    settings: &Settings,
+    skips: &Skips,
     formatter: &mut F,
     replacements: &mut Vec<(Range, String)>,
 ) where
     F: FnMut(&str, bool) -> Result<String>,
 {
+    for query in &settings.queries {
+        let Some(sql_capture) = query.capture_index_for_name(SQL_CAPTURE) else {
+            continue;
+        };
+
+        let mut literals = Vec::new();
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(query, node, source);
+        while let Some(query_match) = matches.next() {
+            literals.extend(
+                query_match
+                    .captures
+                    .iter()
+                    .filter(|capture| capture.index == sql_capture)
+                    .map(|capture| capture.node),
+            );
+        }
+
+        for literal in literals {
+            // literals can be matched by multiple queries or macros
+            if skips.contains(&literal)
+                || replacements
+                    .iter()
+                    .any(|(range, _)| range.start_byte == literal.start_byte())
+            {
+                continue;
+            }
+            collect_literal(literal, source, settings, formatter, replacements);
+        }
+    }
+}
+
+fn parse_token_tree(token_tree: Node, source: &[u8]) -> Option<Tree> {
+    // parsing only the range of the token tree keeps byte offsets and
+    // columns of the nested nodes relative to the whole source
+
+    let mut parser = rust_parser();
+    if let Err(e) = parser.set_included_ranges(&[token_tree.range()]) {
+        error!("failed to set token tree range for re-parsing, error: {e:?}");
+        return None;
+    }
+    let tree = parser.parse(source, None);
+    if tree.is_none() {
+        error!(
+            "failed to re-parse token tree: {:?}",
+            token_tree.utf8_text(source)
+        );
+    }
+
+    tree
+}
+
+pub fn find_sql_references(source: &str, settings: &Settings) -> Vec<SqlReference> {
+    let mut parser = rust_parser();
+    let tree = parser
+        .parse(source.as_bytes(), None)
+        .expect("Failed to parse code");
+
+    let mut references = Vec::new();
+    let aliases = Aliases::collect(tree.root_node(), source.as_bytes());
+    find_references(
+        tree.root_node(),
+        source.as_bytes(),
+        settings,
+        &aliases,
+        &mut references,
+    );
+
+    references
+}
+
+fn find_references(
+    node: Node,
+    source: &[u8],
+    settings: &Settings,
+    aliases: &Aliases,
+    references: &mut Vec<SqlReference>,
+) {
     let mut cursor = node.walk();
 
     for child in node.children(&mut cursor) {
@@ -69,62 +1372,131 @@ fn find_and_collect<'a, F>(
             let macro_name = macro_node
                 .utf8_text(source)
                 .expect("failed to get macro name as utf8");
-            if macro_names.contains(&macro_name.to_string()) {
-                let mut cursor = child.walk();
-                for macro_child in child.children(&mut cursor) {
-                    // raw string literal
+            let mut cursor = child.walk();
+            let token_tree = child
+                .children(&mut cursor)
+                .find(|n| n.kind() == "token_tree");
 
-                    let cursor = &mut macro_child.walk();
-                    if let Some(raw_string_literal) = macro_child
-                        .children(cursor)
-                        .find(|n| n.kind() == "raw_string_literal")
-                    {
-                        match format_raw_string_literal(source, &raw_string_literal, formatter) {
-                            Ok(v) => replacements.push((raw_string_literal.range(), v)),
-                            Err(e) => {
-                                error!(
-                                    "failed to format raw string literal: {:?}, error: {:?}",
-                                    raw_string_literal.utf8_text(source),
-                                    e
-                                );
-                            }
-                        }
-                    }
+            if matches(&settings.query_file_macro_names, macro_name, aliases) {
+                if let Some(path) = token_tree.and_then(|n| first_literal_value(&n, source)) {
+                    references.push(SqlReference::QueryFile(path));
+                }
+            } else if matches(&settings.migrate_macro_names, macro_name, aliases) {
+                let path = token_tree.and_then(|n| first_literal_value(&n, source));
+                references.push(SqlReference::Migrations(path));
+            } else if let Some(token_tree) = token_tree
+                && let Some(tree) = parse_token_tree(token_tree, source)
+            {
+                find_references(tree.root_node(), source, settings, aliases, references);
+            }
+        } else if child.kind() == "attribute"
+            && let Some(path) = child.named_child(0)
+            && let Ok(attribute_name) = path.utf8_text(source)
+            && matches(&settings.test_attribute_names, attribute_name, aliases)
+            && let Some(arguments) = child.child_by_field_name("arguments")
+        {
+            references.extend(
+                fixture_paths(&arguments, source)
+                    .into_iter()
+                    .map(SqlReference::Fixture),
+            );
+        }
+        find_references(child, source, settings, aliases, references);
+    }
+}
+
+/// Resolves the fixtures of test attribute arguments like sqlx does, supporting
+/// `fixtures("users")`, `fixtures("../fixtures/users.sql")` and
+/// `fixtures(path = "../fixtures", scripts("users"))`
+fn fixture_paths(arguments: &Node, source: &[u8]) -> Vec<String> {
+    let mut paths = Vec::new();
 
-                    // string literal
+    let mut cursor = arguments.walk();
+    let children: Vec<Node> = arguments.children(&mut cursor).collect();
 
-                    if let Some(string_literal) = macro_child
-                        .children(cursor)
-                        .find(|n| n.kind() == "string_literal")
+    for pair in children.windows(2) {
+        let [name, fixtures] = pair else { continue };
+        if name.utf8_text(source) != Ok("fixtures") || fixtures.kind() != "token_tree" {
+            continue;
+        }
+
+        let mut cursor = fixtures.walk();
+        let items: Vec<Node> = fixtures.children(&mut cursor).collect();
+
+        let scripts = items.windows(2).find_map(|pair| match pair {
+            [name, scripts]
+                if name.utf8_text(source) == Ok("scripts") && scripts.kind() == "token_tree" =>
+            {
+                Some(literal_values(scripts, source))
+            }
+            _ => None,
+        });
+
+        if let Some(scripts) = scripts {
+            let dir = items
+                .windows(3)
+                .find_map(|w| match w {
+                    [name, eq, value]
+                        if name.utf8_text(source) == Ok("path") && eq.kind() == "=" =>
                     {
-                        match format_string_literal(source, &string_literal, formatter) {
-                            Ok(v) => replacements.push((string_literal.range(), v)),
-                            Err(e) => {
-                                error!(
-                                    "failed to format string literal: {:?}, error: {:?}",
-                                    string_literal.utf8_text(source),
-                                    e
-                                );
-                            }
-                        }
+                        literal_value(value, source)
                     }
+                    _ => None,
+                })
+                .unwrap_or("./fixtures".to_string());
+
+            for script in scripts {
+                let script = if script.ends_with(".sql") {
+                    script
+                } else {
+                    format!("{script}.sql")
+                };
+                paths.push(format!("{}/{script}", dir.trim_end_matches('/')));
+            }
+        } else {
+            for name in literal_values(fixtures, source) {
+                if name.ends_with(".sql") {
+                    paths.push(name);
+                } else {
+                    paths.push(format!("./fixtures/{name}.sql"));
                 }
             }
         }
-        find_and_collect(
-            child,
-            source,
-            macro_names,
-            _literal_indentation,
-            formatter,
-            replacements,
-        );
     }
+
+    paths
+}
+
+/// Returns the unquoted value of the first string or raw string literal child of `node`
+fn first_literal_value(node: &Node, source: &[u8]) -> Option<String> {
+    literal_values(node, source).into_iter().next()
+}
+
+/// Returns the unquoted values of all string and raw string literal children of `node`
+fn literal_values(node: &Node, source: &[u8]) -> Vec<String> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter_map(|n| literal_value(&n, source))
+        .collect()
+}
+
+/// Returns the unquoted value of a string or raw string literal
+fn literal_value(literal: &Node, source: &[u8]) -> Option<String> {
+    let text = literal.utf8_text(source).ok()?;
+
+    let value = match literal.kind() {
+        "raw_string_literal" => unquote_raw_string_literal(text).0,
+        "string_literal" => return unescape_string(&text[1..text.len() - 1]).ok(),
+        _ => return None,
+    };
+
+    Some(value.to_string())
 }
 
 fn format_raw_string_literal<'a>(
     source: &'a [u8],
     raw_string_literal: &Node<'a>,
+    settings: &Settings,
     formatter: &mut impl FnMut(&str, bool) -> Result<String>,
 ) -> Result<String> {
     let literal = raw_string_literal
@@ -136,6 +1508,18 @@ fn format_raw_string_literal<'a>(
     let (unquoted, hash_count) = unquote_raw_string_literal(literal);
 
     let col: usize = raw_string_literal.start_position().column;
+    let indentation = match settings.layout {
+        Layout::MacroRelative => enclosing_indentation(source, raw_string_literal),
+        _ => column_indentation(source, raw_string_literal, settings),
+    };
+    let body_indentation = match settings.layout {
+        Layout::Aligned => indentation.clone(),
+        _ => format!("{indentation}{}", indentation_unit(settings)),
+    };
+    let unquote = match settings.layout {
+        Layout::Hanging => format!("\"{}", "#".repeat(hash_count)),
+        _ => format!("\n{indentation}\"{}", "#".repeat(hash_count)),
+    };
 
     let formatter_res = formatter(unquoted, true);
     let Ok(replacement) = formatter_res else {
@@ -151,55 +1535,65 @@ fn format_raw_string_literal<'a>(
         "raw string literal => col: {col}, literal_lines: {literal_text_lines_count}, replacement_lines_count: {replacement_line_count}"
     );
 
-    let new_literal = if literal_text_lines_count <= 1 && replacement_line_count > 1 {
+    // sql that fits in `single_line_max_width` from the literal's column is collapsed to one line,
+    // without a max width sql stays on one line if the formatter returned a single line
+    let single_line = match settings.single_line_max_width {
+        Some(max_width) => collapse_lines(&replacement).filter(|sql| {
+            let hashes = "#".repeat(hash_count);
+            display_column(source, raw_string_literal, settings)
+                + display_width(&format!("r{hashes}\"{sql}\"{hashes}"), settings.tab_width)
+                <= max_width
+        }),
+        None => (replacement_line_count <= 1).then(|| replacement.trim().to_string()),
+    };
+
+    let new_literal = if let Some(sql) = single_line {
+        debug!("RAW_SINGLE detected");
+        format!(
+            "{quote}{reappearance}{unquote}",
+            quote = format!("r{}\"", "#".repeat(hash_count)),
+            reappearance = sql,
+            unquote = format!("\"{}", "#".repeat(hash_count))
+        )
+    } else if literal_text_lines_count <= 1 {
         debug!("RAW_SINGLE_TO_MANY detected");
         format!(
-            "{quote}{replacement}\n{unquote}",
+            "{quote}{replacement}{unquote}",
             quote = format!("r{}\"\n", "#".repeat(hash_count)),
             replacement = replacement
                 .lines()
                 .map(|line| format!(
                     "{}{}",
                     if !line.trim().is_empty() {
-                        " ".repeat(col.saturating_add(4))
+                        body_indentation.as_str()
                     } else {
-                        "".to_string()
+                        ""
                     },
                     line
                 ))
                 .collect::<Vec<String>>()
                 .join("\n")
                 .trim_end(),
-            unquote = format!("{}\"{}", " ".repeat(col), "#".repeat(hash_count))
-        )
-    } else if replacement.lines().count() <= 1 {
-        debug!("RAW_SINGLE detected");
-        format!(
-            "{quote}{reappearance}{unquote}",
-            quote = format!("r{}\"", "#".repeat(hash_count)),
-            reappearance = replacement.trim(),
-            unquote = format!("\"{}", "#".repeat(hash_count))
         )
     } else {
         debug!("RAW_MANY detected");
         format!(
-            "{quote}{replacement}\n{unquote}",
+            "{quote}{replacement}{unquote}",
             quote = format!("r{}\"\n", "#".repeat(hash_count)),
             replacement = replacement
                 .lines()
                 .map(|line| format!(
                     "{}{}",
                     if !line.trim().is_empty() {
-                        " ".repeat(col.saturating_add(4))
+                        body_indentation.as_str()
                     } else {
-                        "".to_string()
+                        ""
                     },
                     line
                 ))
                 .collect::<Vec<String>>()
                 .join("\n")
                 .trim_end(),
-            unquote = format!("{}\"{}", " ".repeat(col), "#".repeat(hash_count))
         )
     };
 
@@ -209,6 +1603,7 @@ fn format_raw_string_literal<'a>(
 fn format_string_literal<'a>(
     source: &'a [u8],
     string_literal: &Node<'a>,
+    settings: &Settings,
     formatter: &mut impl FnMut(&str, bool) -> Result<String>,
 ) -> Result<String> {
     let literal = string_literal
@@ -217,11 +1612,12 @@ fn format_string_literal<'a>(
         .trim();
 
     let literal_text_lines_count = literal.lines().count();
-    let unquoted = &literal[1..literal.len() - 1];
+    let unquoted = unescape_string(&literal[1..literal.len() - 1])?;
 
     let col: usize = string_literal.start_position().column;
+    let indentation = column_indentation(source, string_literal, settings);
 
-    let formatter_res = formatter(unquoted, true);
+    let formatter_res = formatter(&unquoted, true);
     let Ok(replacement) = formatter_res else {
         bail!(
             "formatter failed to format sql {unquoted}, error: {:?}",
@@ -235,18 +1631,230 @@ fn format_string_literal<'a>(
         "string literal => col: {col}, literal_lines: {literal_text_lines_count}, replacement_lines_count: {replacement_line_count}"
     );
 
-    let new_literal = format!(
-        "\"{replacement}\"",
-        replacement = replacement
-            .lines()
-            .map(|l| l.trim())
-            .collect::<Vec<_>>()
-            .join(" ")
-    );
+    // a line continuation drops the line break and the whitespace following it, so lines
+    // with a `--` comment or a quoted value spanning them are kept apart with `\n` instead,
+    // see STRING_ESCAPED_LINES
+
+    let new_literal = if settings.string_style == StringStyle::Continuation
+        && replacement.trim().lines().count() > 1
+        && !has_line_comment(&replacement)
+        && !has_quoted_line_break(&replacement)
+    {
+        debug!("STRING_CONTINUATION detected");
+        // whitespace following a line continuation is not part of the string,
+        // so lines keep their sql indentation relative to the opening quote
+        format!(
+            "\"{replacement}\"",
+            replacement = replacement
+                .trim()
+                .lines()
+                .enumerate()
+                .map(|(i, line)| format!(
+                    "{}{}",
+                    if i > 0 {
+                        format!("{indentation} ")
+                    } else {
+                        "".to_string()
+                    },
+                    escape_string(line.trim_end())
+                ))
+                .filter(|line| !line.trim().is_empty())
+                .collect::<Vec<String>>()
+                .join(" \\\n")
+        )
+    } else if has_line_comment(&replacement) || has_quoted_line_break(&replacement) {
+        debug!("STRING_ESCAPED_LINES detected");
+        // joined lines would end up in the comment or change a quoted value,
+        // so the line breaks are kept as `\n`
+        format!("\"{}\"", escape_string(replacement.trim_end()))
+    } else {
+        format!(
+            "\"{replacement}\"",
+            replacement = escape_string(
+                &replacement
+                    .lines()
+                    .map(|l| l.trim())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            )
+        )
+    };
 
     Ok(new_literal)
 }
 
+/// Whitespace reaching the column `node` starts at, the leading whitespace of its line is kept
+/// and spaces align with the code in front of it by display width, e.g. of `🦀` or tabs
+fn column_indentation(source: &[u8], node: &Node, settings: &Settings) -> String {
+    let prefix = line_prefix(source, node);
+    let leading_whitespace = &prefix[..prefix.len() - prefix.trim_start().len()];
+
+    let alignment = display_width(&prefix, settings.tab_width)
+        .saturating_sub(display_width(leading_whitespace, settings.tab_width));
+
+    format!("{leading_whitespace}{}", " ".repeat(alignment))
+}
+
+/// Display column `node` starts at
+fn display_column(source: &[u8], node: &Node, settings: &Settings) -> usize {
+    display_width(&line_prefix(source, node), settings.tab_width)
+}
+
+/// The part of the line in front of `node`
+fn line_prefix<'a>(source: &'a [u8], node: &Node) -> std::borrow::Cow<'a, str> {
+    // tree-sitter columns are bytes
+    let line_start = node.start_byte() - node.start_position().column;
+    String::from_utf8_lossy(&source[line_start..node.start_byte()])
+}
+
+/// Joins the lines of formatted sql with single spaces, `None` if a `--` comment
+/// would swallow the lines following it or a quoted value spans lines
+fn collapse_lines(sql: &str) -> Option<String> {
+    if has_line_comment(sql) || has_quoted_line_break(sql) {
+        return None;
+    }
+
+    Some(
+        sql.lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+/// Checks if sql has a `--` comment running to the end of its line
+fn has_line_comment(sql: &str) -> bool {
+    sql.lines().any(|line| line.contains("--"))
+}
+
+/// Checks if sql has a line break inside a quoted value or identifier, e.g. `'line1\nline2'`
+fn has_quoted_line_break(sql: &str) -> bool {
+    let mut quote = None;
+    let mut chars = sql.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), '\n') => return true,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            // quotes in comments like `-- don't` do not start a value
+            (None, '-') if chars.peek() == Some(&'-') => {
+                while chars.next_if(|&c| c != '\n').is_some() {}
+            }
+            (None, _) => {}
+        }
+    }
+
+    false
+}
+
+/// Columns `text` takes up at the start of a line
+fn display_width(text: &str, tab_width: usize) -> usize {
+    text.chars().fold(0, |width, c| match c {
+        '\t' => (width / tab_width.max(1) + 1) * tab_width.max(1),
+        c => width + c.width().unwrap_or(0),
+    })
+}
+
+/// Leading whitespace of the line the macro or function call a literal is passed to starts on,
+/// or of the literal's own line if it is not passed to one
+fn enclosing_indentation(source: &[u8], node: &Node) -> String {
+    let mut enclosing = node.parent();
+    while let Some(parent) = enclosing
+        && !matches!(parent.kind(), "macro_invocation" | "call_expression")
+    {
+        enclosing = parent.parent();
+    }
+    let enclosing = enclosing.unwrap_or(*node);
+
+    let line_start = enclosing.start_byte() - enclosing.start_position().column;
+    let leading_whitespace = source[line_start..enclosing.start_byte()]
+        .iter()
+        .take_while(|b| matches!(b, b' ' | b'\t'))
+        .count();
+
+    String::from_utf8_lossy(&source[line_start..line_start + leading_whitespace]).to_string()
+}
+
+/// Whitespace the sql is indented by relative to its literal
+fn indentation_unit(settings: &Settings) -> String {
+    match settings.indent_style {
+        IndentStyle::Spaces => " ".repeat(settings.literal_indentation),
+        IndentStyle::Tabs => "\t".to_string(),
+    }
+}
+
+/// Decodes the escape sequences of a string literal's content, e.g. `\"one!\"` => `"one!"`
+fn unescape_string(escaped: &str) -> Result<String> {
+    let mut unescaped = String::with_capacity(escaped.len());
+    let mut chars = escaped.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        let c = match chars.next() {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('0') => '\0',
+            Some('\\') => '\\',
+            Some('\'') => '\'',
+            Some('"') => '"',
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                match u8::from_str_radix(&hex, 16) {
+                    Ok(b) if b.is_ascii() => b as char,
+                    _ => bail!("invalid escape sequence \\x{hex}"),
+                }
+            }
+            Some('u') => {
+                let unicode: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                let hex = unicode.trim_start_matches('{').replace('_', "");
+                match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                    Some(c) => c,
+                    None => bail!("invalid escape sequence \\u{unicode}}}"),
+                }
+            }
+            // line continuations skip the line break and the following whitespace
+            Some('\n') | Some('\r') => {
+                let rest = chars.as_str().trim_start_matches([' ', '\t', '\n', '\r']);
+                chars = rest.chars();
+                continue;
+            }
+            Some(c) => bail!("unsupported escape sequence \\{}", c.escape_debug()),
+            None => bail!("string literal ends with a backslash"),
+        };
+        unescaped.push(c);
+    }
+
+    Ok(unescaped)
+}
+
+/// Encodes a string as the content of a string literal, the reverse of [`unescape_string`]
+fn escape_string(unescaped: &str) -> String {
+    let mut escaped = String::with_capacity(unescaped.len());
+
+    for c in unescaped.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\0' => escaped.push_str("\\0"),
+            c if c.is_control() => escaped.push_str(&c.escape_unicode().to_string()),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
 fn unquote_raw_string_literal(lit: &str) -> (&str, usize) {
     // r#"..."#, r##"..."##, etc.
     let og_hashes = lit[1..].find('"').expect("invalid raw string literal");
@@ -6,7 +6,7 @@ pub fn compare(expected: &str, formatted: &str) {
 
         panic!(
             "formatted content does not match expected: \n{:#?}",
-            diff(&expected, &formatted, "")
+            diff(expected, formatted, "")
         );
     }
 }
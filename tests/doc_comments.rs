@@ -0,0 +1,73 @@
+mod common;
+
+#[test_log::test]
+fn doc_comments() {
+    let content = r###"
+    /// Finds a test by id
+    ///
+    /// ```no_run
+    /// # async fn example(pool: sqlx::PgPool) -> sqlx::Result<()> {
+    /// let test = sqlx::query!("select *   from test where id = '1';")
+    ///     .fetch_one(&pool)
+    ///     .await?;
+    /// let test = sqlx::query!(
+    ///     r#"
+    ///         select   *
+    ///             from
+    ///                 test where id = $1
+    ///     "#,
+    ///     id
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// ```sql
+    /// select *   from test where id = '1';
+    /// ```
+    fn find() {
+        sqlx::query!("select *   from test where id = '1';");
+    }
+    "###;
+
+    let expected = r###"
+    /// Finds a test by id
+    ///
+    /// ```no_run
+    /// # async fn example(pool: sqlx::PgPool) -> sqlx::Result<()> {
+    /// let test = sqlx::query!("select * from test where id = '1';")
+    ///     .fetch_one(&pool)
+    ///     .await?;
+    /// let test = sqlx::query!(
+    ///     r#"
+    ///         select *
+    ///         from
+    ///             test
+    ///         where id = $1
+    ///     "#,
+    ///     id
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// ```sql
+    /// select *   from test where id = '1';
+    /// ```
+    fn find() {
+        sqlx::query!("select * from test where id = '1';");
+    }
+    "###;
+
+    let options = sqlx_fmt::Options {
+        doc_comments: true,
+        ..Default::default()
+    };
+    let formatted = sqlx_fmt::format_with_options(content, &options).unwrap();
+    common::compare(expected, &formatted);
+
+    // doc comments are opt-in
+
+    let formatted = sqlx_fmt::format_with_options(content, &Default::default()).unwrap();
+    assert!(formatted.contains("///         select   *"));
+}
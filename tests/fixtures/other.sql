@@ -0,0 +1 @@
+insert into other (id) values (1);
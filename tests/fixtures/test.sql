@@ -0,0 +1 @@
+insert into test (id)   values (1);
@@ -0,0 +1,59 @@
+mod common;
+
+#[test_log::test]
+fn format_template() {
+    let content = r###"
+    sqlx::query(&format!("select {cols}   from {} where id = '{{1}}';", table))
+        .fetch_one(pool)
+        .await?;
+
+    sqlx::query_as::<_, Test>(
+        format!(
+            r#"
+                select   {}
+                    from
+                        {table:?} where id = $1
+            "#,
+            cols
+        )
+        .as_str(),
+    )
+    .bind(id);
+
+    let sql = format!("select {cols}   from {table} where id = '{{1}}';");
+    "###;
+
+    let expected = r###"
+    sqlx::query(&format!("select {cols} from {} where id = '{{1}}';", table))
+        .fetch_one(pool)
+        .await?;
+
+    sqlx::query_as::<_, Test>(
+        format!(
+            r#"
+                select {}
+                from
+                    {table:?}
+                where id = $1
+            "#,
+            cols
+        )
+        .as_str(),
+    )
+    .bind(id);
+
+    let sql = format!("select {cols}   from {table} where id = '{{1}}';");
+    "###;
+
+    let options = sqlx_fmt::Options {
+        format_templates: true,
+        ..Default::default()
+    };
+    let formatted = sqlx_fmt::format_with_options(content, &options).unwrap();
+    common::compare(expected, &formatted);
+
+    // templates are opt-in
+
+    let formatted = sqlx_fmt::format_with_options(content, &Default::default()).unwrap();
+    common::compare(content, &formatted);
+}
@@ -0,0 +1,66 @@
+mod common;
+
+#[test_log::test]
+fn function() {
+    let content = r###"
+    sqlx::query("select *   from test where id = '1';")
+        .fetch_one(pool)
+        .await?;
+
+    sqlx::query_as::<_, Test>(
+        r#"
+            select   *
+                from
+                    test where id = $1
+        "#,
+    )
+    .bind(id)
+    .fetch_one(pool)
+    .await?;
+
+    other::query("select *   from test where id = '1';");
+    "###;
+
+    let expected = r###"
+    sqlx::query("select * from test where id = '1';")
+        .fetch_one(pool)
+        .await?;
+
+    sqlx::query_as::<_, Test>(
+        r#"
+            select *
+            from
+                test
+            where id = $1
+        "#,
+    )
+    .bind(id)
+    .fetch_one(pool)
+    .await?;
+
+    other::query("select *   from test where id = '1';");
+    "###;
+
+    let formatted = sqlx_fmt::format(content, ".sqruff", 4, &None).unwrap();
+    common::compare(expected, &formatted);
+}
+
+#[test_log::test]
+fn function_custom() {
+    let content = r###"
+    db::execute("select *   from test where id = '1';");
+    sqlx::query("select *   from test where id = '1';");
+    "###;
+
+    let expected = r###"
+    db::execute("select * from test where id = '1';");
+    sqlx::query("select *   from test where id = '1';");
+    "###;
+
+    let options = sqlx_fmt::Options {
+        functions: Some("db::execute".to_string()),
+        ..Default::default()
+    };
+    let formatted = sqlx_fmt::format_with_options(content, &options).unwrap();
+    common::compare(expected, &formatted);
+}
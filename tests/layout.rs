@@ -0,0 +1,111 @@
+mod common;
+
+use sqlx_fmt::tree_sitter::Layout;
+
+const CONTENT: &str = r###"
+    let query = sqlx::query!(
+        r#"
+        select   *
+            from
+                test where id = $1
+        "#
+    );
+    let users = sqlx::query!(r#"select   *   from test where id = $1"#);
+    "###;
+
+fn format(layout: Layout) -> String {
+    sqlx_fmt::format_with_options(
+        CONTENT,
+        &sqlx_fmt::Options {
+            layout,
+            ..Default::default()
+        },
+    )
+    .unwrap()
+}
+
+#[test_log::test]
+fn layout_block() {
+    let expected = r###"
+    let query = sqlx::query!(
+        r#"
+            select *
+            from
+                test
+            where id = $1
+        "#
+    );
+    let users = sqlx::query!(r#"
+                                 select *
+                                 from
+                                     test
+                                 where id = $1
+                             "#);
+    "###;
+
+    common::compare(expected, &format(Layout::Block));
+}
+
+#[test_log::test]
+fn layout_aligned() {
+    let expected = r###"
+    let query = sqlx::query!(
+        r#"
+        select *
+        from
+            test
+        where id = $1
+        "#
+    );
+    let users = sqlx::query!(r#"
+                             select *
+                             from
+                                 test
+                             where id = $1
+                             "#);
+    "###;
+
+    common::compare(expected, &format(Layout::Aligned));
+}
+
+#[test_log::test]
+fn layout_hanging() {
+    let expected = r###"
+    let query = sqlx::query!(
+        r#"
+            select *
+            from
+                test
+            where id = $1"#
+    );
+    let users = sqlx::query!(r#"
+                                 select *
+                                 from
+                                     test
+                                 where id = $1"#);
+    "###;
+
+    common::compare(expected, &format(Layout::Hanging));
+}
+
+#[test_log::test]
+fn layout_macro_relative() {
+    let expected = r###"
+    let query = sqlx::query!(
+        r#"
+        select *
+        from
+            test
+        where id = $1
+    "#
+    );
+    let users = sqlx::query!(r#"
+        select *
+        from
+            test
+        where id = $1
+    "#);
+    "###;
+
+    common::compare(expected, &format(Layout::MacroRelative));
+}
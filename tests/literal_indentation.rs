@@ -0,0 +1,92 @@
+mod common;
+
+#[test_log::test]
+fn literal_indentation() {
+    let content = r###"
+  let query = sqlx::query!(
+    r#"
+      select   *
+          from
+              test where id = $1
+    "#
+  );
+  "###;
+
+    let expected = r###"
+  let query = sqlx::query!(
+    r#"
+      select *
+      from
+          test
+      where id = $1
+    "#
+  );
+  "###;
+
+    let formatted = sqlx_fmt::format(content, ".sqruff", 2, &None).unwrap();
+    common::compare(expected, &formatted);
+}
+
+#[test_log::test]
+fn literal_indentation_tabs() {
+    let content = "
+\tlet query = sqlx::query!(
+\t\tr#\"
+\t\t\tselect   *
+\t\t\t\tfrom
+\t\t\t\ttest where id = $1
+\t\t\"#
+\t);
+";
+
+    let expected = "
+\tlet query = sqlx::query!(
+\t\tr#\"
+\t\t\tselect *
+\t\t\tfrom
+\t\t\t    test
+\t\t\twhere id = $1
+\t\t\"#
+\t);
+";
+
+    let formatted = sqlx_fmt::format_with_options(
+        content,
+        &sqlx_fmt::Options {
+            indent_style: sqlx_fmt::tree_sitter::IndentStyle::Tabs,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    common::compare(expected, &formatted);
+}
+
+#[test_log::test]
+fn literal_indentation_default_settings() {
+    let content = r###"
+    let query = sqlx::query!(
+        r#"
+        select 1
+        "#
+    );
+    "###;
+
+    let expected = r###"
+    let query = sqlx::query!(
+        r#"
+            select 1
+            from test
+        "#
+    );
+    "###;
+
+    let settings = sqlx_fmt::tree_sitter::Settings {
+        macro_names: vec![sqlx_fmt::pattern::Matcher::from("sqlx::query")],
+        ..Default::default()
+    };
+    let formatted =
+        sqlx_fmt::tree_sitter::format_query_macros_literals(content, &settings, |_sql, _is_raw| {
+            Ok("select 1\nfrom test\n".to_string())
+        });
+    common::compare(expected, &formatted);
+}
@@ -0,0 +1,24 @@
+mod common;
+
+#[test_log::test]
+fn macro_argument() {
+    let content = r###"
+    cached_query!("users:   by-id", "select *   from test where id = '1';", id);
+    audit_query!(table = "users   log", sql = "select *   from test where id = '1';");
+    db::fetch("users:   by-id", "select *   from test where id = '1';");
+    "###;
+
+    let expected = r###"
+    cached_query!("users:   by-id", "select * from test where id = '1';", id);
+    audit_query!(table = "users   log", sql = "select * from test where id = '1';");
+    db::fetch("users:   by-id", "select * from test where id = '1';");
+    "###;
+
+    let options = sqlx_fmt::Options {
+        macros: Some("cached_query@1, audit_query@sql".to_string()),
+        functions: Some("db::fetch@1".to_string()),
+        ..Default::default()
+    };
+    let formatted = sqlx_fmt::format_with_options(content, &options).unwrap();
+    common::compare(expected, &formatted);
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use sqlx_fmt::pattern::{Matcher, Pattern};
+use sqlx_fmt::tree_sitter::{Settings, format_query_macros_literals};
+
+#[test_log::test]
+fn macro_patterns() {
+    let content = r###"
+    sqlx::query!("select *   from test where id = '1';");
+    db::query_logged!("select *   from test where id = '1';");
+    tenant_query_as!(Test, "select *   from test where id = '1';");
+    tenant_query_as_cached!(Test, "select *   from test where id = '1';");
+    "###;
+
+    let expected = r###"
+    sqlx::query!("select * from test where id = '1';");
+    db::query_logged!("select * from test where id = '1';");
+    tenant_query_as!(Test, "select * from test where id = '1';");
+    tenant_query_as_cached!(Test, "select *   from test where id = '1';");
+    "###;
+
+    let macros = Some("sqlx::query, *::query_*, re:^tenant_.*query(_as)?$".to_string());
+    let formatted = sqlx_fmt::format(content, ".sqruff", 4, &macros).unwrap();
+    common::compare(expected, &formatted);
+}
+
+#[test_log::test]
+fn macro_patterns_invalid_regex() {
+    let macros = Some("re:^tenant_(".to_string());
+    assert!(sqlx_fmt::format("", ".sqruff", 4, &macros).is_err());
+}
+
+#[test_log::test]
+fn macro_patterns_skip_paths() {
+    let content = r###"
+    sqlx::query!("select *   from test where id = '1';");
+    sqlx::query_file!("queries/find_user.sql");
+    sqlx::query_file_as!(User, "queries/find_user.sql");
+    sqlx::migrate!("./migrations");
+    "###;
+
+    let expected = r###"
+    sqlx::query!("select 1");
+    sqlx::query_file!("queries/find_user.sql");
+    sqlx::query_file_as!(User, "queries/find_user.sql");
+    sqlx::migrate!("./migrations");
+    "###;
+
+    let settings = Settings {
+        macro_names: vec![
+            Matcher::parse("*::query*").unwrap(),
+            Matcher::parse("*::migrate").unwrap(),
+        ],
+        query_file_macro_names: vec![
+            Pattern::from("sqlx::query_file"),
+            Pattern::from("sqlx::query_file_as"),
+        ],
+        migrate_macro_names: vec![Pattern::from("sqlx::migrate")],
+        ..Default::default()
+    };
+
+    let mut formatted_sql = Vec::new();
+    let formatted = format_query_macros_literals(content, &settings, |sql, _is_raw| {
+        formatted_sql.push(sql.to_string());
+        Ok("select 1".to_string())
+    });
+
+    common::compare(expected, &formatted);
+    assert_eq!(formatted_sql, vec!["select *   from test where id = '1';"]);
+}
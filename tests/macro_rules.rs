@@ -0,0 +1,61 @@
+mod common;
+
+const CONTENT: &str = r###"
+    macro_rules! find_by_id {
+        ($name:ident, $ty:ty, $table:literal) => {
+            pub async fn $name(pool: &PgPool, id: i64) -> Result<$ty> {
+                sqlx::query_as!(
+                    $ty,
+                    r#"
+                    select   *
+                        from
+                            test where id = $1
+                    "#,
+                    id
+                )
+                .fetch_one(pool)
+                .await
+            }
+        };
+    }
+    "###;
+
+#[test_log::test]
+fn macro_rules() {
+    let expected = r###"
+    macro_rules! find_by_id {
+        ($name:ident, $ty:ty, $table:literal) => {
+            pub async fn $name(pool: &PgPool, id: i64) -> Result<$ty> {
+                sqlx::query_as!(
+                    $ty,
+                    r#"
+                        select *
+                        from
+                            test
+                        where id = $1
+                    "#,
+                    id
+                )
+                .fetch_one(pool)
+                .await
+            }
+        };
+    }
+    "###;
+
+    let formatted = sqlx_fmt::format_with_options(
+        CONTENT,
+        &sqlx_fmt::Options {
+            macro_rules: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    common::compare(expected, &formatted);
+}
+
+#[test_log::test]
+fn macro_rules_disabled() {
+    let formatted = sqlx_fmt::format(CONTENT, ".sqruff", 4, &None).unwrap();
+    common::compare(CONTENT, &formatted);
+}
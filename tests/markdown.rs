@@ -0,0 +1,94 @@
+mod common;
+
+#[test_log::test]
+fn markdown() {
+    let content = r###"# Queries
+
+Find a test   by id:
+
+```rust,no_run
+let test = sqlx::query!("select *   from test where id = '1';")
+    .fetch_one(&pool)
+    .await?;
+```
+
+- in a list:
+
+  ```sql
+  select   *
+      from
+          test where id = $1
+  ```
+
+```text
+select *   from test where id = '1';
+```
+
+~~~
+sqlx::query!("select *   from test where id = '1';")
+~~~
+"###;
+
+    let expected = r###"# Queries
+
+Find a test   by id:
+
+```rust,no_run
+let test = sqlx::query!("select * from test where id = '1';")
+    .fetch_one(&pool)
+    .await?;
+```
+
+- in a list:
+
+  ```sql
+  select *
+  from
+      test
+  where id = $1
+  ```
+
+```text
+select *   from test where id = '1';
+```
+
+~~~
+sqlx::query!("select *   from test where id = '1';")
+~~~
+"###;
+
+    let formatted = sqlx_fmt::format_markdown(content, &Default::default()).unwrap();
+    common::compare(expected, &formatted);
+}
+
+#[test_log::test]
+fn markdown_hidden_lines() {
+    let content = r###"# Queries
+
+```rust
+# use sqlx::PgPool;
+# async fn find_test(pool: &PgPool) -> sqlx::Result<()> {
+let test = sqlx::query!("select *   from test where id = '1';")
+    .fetch_one(pool)
+    .await?;
+# Ok(())
+# }
+```
+"###;
+
+    let expected = r###"# Queries
+
+```rust
+# use sqlx::PgPool;
+# async fn find_test(pool: &PgPool) -> sqlx::Result<()> {
+let test = sqlx::query!("select * from test where id = '1';")
+    .fetch_one(pool)
+    .await?;
+# Ok(())
+# }
+```
+"###;
+
+    let formatted = sqlx_fmt::format_markdown(content, &Default::default()).unwrap();
+    common::compare(expected, &formatted);
+}
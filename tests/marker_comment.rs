@@ -0,0 +1,77 @@
+mod common;
+
+#[test_log::test]
+fn marker_comment() {
+    let content = r###"
+    // language=sql
+    const FIND_TEST: &str = "select *   from test where id = '1';";
+
+    const FIND_OTHER: &str = /* sql */
+        r#"
+            select   *
+                from
+                    test where id = $1
+        "#;
+
+    let mut builder = QueryBuilder::new(/* SQL */ "select *   from test where id = '1';");
+
+    // language=PostgreSQL
+    let query = format!("select *   from test where id = '1';");
+
+    // language=json
+    let key = format!("users:   {id}");
+    let name = "not   sql";
+    "###;
+
+    let expected = r###"
+    // language=sql
+    const FIND_TEST: &str = "select * from test where id = '1';";
+
+    const FIND_OTHER: &str = /* sql */
+        r#"
+            select *
+            from
+                test
+            where id = $1
+        "#;
+
+    let mut builder = QueryBuilder::new(/* SQL */ "select * from test where id = '1';");
+
+    // language=PostgreSQL
+    let query = format!("select * from test where id = '1';");
+
+    // language=json
+    let key = format!("users:   {id}");
+    let name = "not   sql";
+    "###;
+
+    let formatted = sqlx_fmt::format(content, ".sqruff", 4, &None).unwrap();
+    common::compare(expected, &formatted);
+}
+
+#[test_log::test]
+fn marker_comment_plain_comment() {
+    let content = r###"
+    // SQL
+    let greeting = "hello   world";
+
+    // sql
+    let farewell = r#"bye   world"#;
+    "###;
+
+    let formatted = sqlx_fmt::format(content, ".sqruff", 4, &None).unwrap();
+    common::compare(content, &formatted);
+}
+
+#[test_log::test]
+fn marker_comment_byte_strings() {
+    let content = r###"
+    const FIND_BYTES: &[u8] = /* sql */ b"select *   from test";
+    const FIND_RAW_BYTES: &[u8] = /* sql */ br#"select *   from test"#;
+    const FIND_C: &CStr = /* sql */ c"select *   from test";
+    const FIND_RAW_C: &CStr = /* sql */ cr#"select *   from test"#;
+    "###;
+
+    let formatted = sqlx_fmt::format(content, ".sqruff", 4, &None).unwrap();
+    common::compare(content, &formatted);
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use std::path::Path;
+
+#[test_log::test]
+fn migrate() {
+    let content = r###"
+    sqlx::migrate!("tests/migrations").run(pool).await?;
+    sqlx::migrate!().run(pool).await?;
+    "###;
+
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .canonicalize()
+        .unwrap();
+    let options = sqlx_fmt::Options {
+        migrations: true,
+        ..Default::default()
+    };
+
+    // the directory literal is not formatted as sql
+
+    let formatted = sqlx_fmt::format_with_options(content, &options).unwrap();
+    common::compare(content, &formatted);
+
+    // the migrations in it are, the default `./migrations` does not exist
+
+    let sql_files =
+        sqlx_fmt::find_sql_files(content, &manifest_dir.join("src/lib.rs"), &options).unwrap();
+    assert_eq!(
+        sql_files,
+        vec![
+            manifest_dir.join("tests/migrations/0001_test.down.sql"),
+            manifest_dir.join("tests/migrations/0001_test.up.sql"),
+            manifest_dir.join("tests/migrations/0002_log.sql"),
+        ]
+    );
+
+    let sql = std::fs::read_to_string(&sql_files[2]).unwrap();
+    let formatted = sqlx_fmt::format_sql(&sql, &options).unwrap();
+
+    let expected = "alter table test add column log text\n";
+    common::compare(expected, &formatted);
+}
+
+#[test_log::test]
+fn migrate_not_enabled() {
+    let content = r###"
+    sqlx::migrate!("tests/migrations").run(pool).await?;
+    "###;
+
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .canonicalize()
+        .unwrap();
+    let options = sqlx_fmt::Options::default();
+
+    // applied migrations are checksummed by sqlx, they are only formatted on request
+
+    let sql_files =
+        sqlx_fmt::find_sql_files(content, &manifest_dir.join("src/lib.rs"), &options).unwrap();
+    assert!(sql_files.is_empty());
+}
@@ -0,0 +1 @@
+alter table    test add column log text
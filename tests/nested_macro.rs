@@ -0,0 +1,54 @@
+mod common;
+
+#[test_log::test]
+fn nested_macro() {
+    let content = r###"
+    let (a, b) = tokio::try_join!(
+        sqlx::query!("select *   from test where id = '1';").fetch_one(pool),
+        sqlx::query_as!(
+            Test,
+            r#"
+                select   *
+                    from
+                        test where id = $1
+            "#,
+            id
+        )
+        .fetch_one(pool),
+    )?;
+    "###;
+
+    let expected = r###"
+    let (a, b) = tokio::try_join!(
+        sqlx::query!("select * from test where id = '1';").fetch_one(pool),
+        sqlx::query_as!(
+            Test,
+            r#"
+                select *
+                from
+                    test
+                where id = $1
+            "#,
+            id
+        )
+        .fetch_one(pool),
+    )?;
+    "###;
+
+    let formatted = sqlx_fmt::format(content, ".sqruff", 4, &None).unwrap();
+    common::compare(expected, &formatted);
+}
+
+#[test_log::test]
+fn nested_macro_deep() {
+    let content = r###"
+    assert!(vec![sqlx::query!("select *   from test where id = '1';")].len() == 1);
+    "###;
+
+    let expected = r###"
+    assert!(vec![sqlx::query!("select * from test where id = '1';")].len() == 1);
+    "###;
+
+    let formatted = sqlx_fmt::format(content, ".sqruff", 4, &None).unwrap();
+    common::compare(expected, &formatted);
+}
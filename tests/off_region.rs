@@ -0,0 +1,56 @@
+mod common;
+
+#[test_log::test]
+fn off_region() {
+    let content = r###"
+    let query = sqlx::query!(
+        r#"
+        select   *
+            from test
+        -- sqlx-fmt: off
+        where id   = $1
+          and name = $2
+        -- sqlx-fmt: on
+        "#
+    );
+    "###;
+
+    let expected = r###"
+    let query = sqlx::query!(
+        r#"
+            select *
+            from test
+            -- sqlx-fmt: off
+            where id   = $1
+              and name = $2
+            -- sqlx-fmt: on
+        "#
+    );
+    "###;
+
+    let formatted = sqlx_fmt::format(content, ".sqruff", 4, &None).unwrap();
+    common::compare(expected, &formatted);
+}
+
+#[test_log::test]
+fn off_region_sql_file() {
+    let content = "select   *\nfrom test\n-- sqlx-fmt: off\nwhere id   = $1\n  and name = $2\n";
+    let expected = "select *\nfrom test\n-- sqlx-fmt: off\nwhere id   = $1\n  and name = $2\n";
+
+    let formatted = sqlx_fmt::format_sql(content, &Default::default()).unwrap();
+    common::compare(expected, &formatted);
+}
+
+#[test_log::test]
+fn off_region_string_literal() {
+    let content = r###"
+    let query = sqlx::query!("select   *\n-- sqlx-fmt: off\nfrom   test\n-- sqlx-fmt: on\nwhere id = $1");
+    "###;
+
+    let expected = r###"
+    let query = sqlx::query!("select *\n-- sqlx-fmt: off\nfrom   test\n-- sqlx-fmt: on\nwhere id = $1");
+    "###;
+
+    let formatted = sqlx_fmt::format(content, ".sqruff", 4, &None).unwrap();
+    common::compare(expected, &formatted);
+}
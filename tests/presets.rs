@@ -0,0 +1,46 @@
+mod common;
+
+#[test_log::test]
+fn presets() {
+    let content = r###"
+    client.query("select *   from test where id = '1';", &[]).await?;
+    tokio_postgres::Client::execute(&client, "select *   from test where id = '1';", &[]).await?;
+    conn.execute("select *   from test where id = '1';", params![])?;
+    diesel::sql_query("select *   from test where id = '1';").load(conn)?;
+    Statement::from_string(backend, "select *   from test where id = '1';");
+    client.query_one(&statement, &[]).await?;
+    cache.insert("users:   by-id", user);
+    "###;
+
+    let expected = r###"
+    client.query("select * from test where id = '1';", &[]).await?;
+    tokio_postgres::Client::execute(&client, "select * from test where id = '1';", &[]).await?;
+    conn.execute("select * from test where id = '1';", params![])?;
+    diesel::sql_query("select * from test where id = '1';").load(conn)?;
+    Statement::from_string(backend, "select * from test where id = '1';");
+    client.query_one(&statement, &[]).await?;
+    cache.insert("users:   by-id", user);
+    "###;
+
+    let options = sqlx_fmt::Options {
+        presets: Some("tokio-postgres, rusqlite, diesel, sea-orm".to_string()),
+        ..Default::default()
+    };
+    let formatted = sqlx_fmt::format_with_options(content, &options).unwrap();
+    common::compare(expected, &formatted);
+
+    // methods are only matched with a preset
+
+    let formatted = sqlx_fmt::format_with_options(content, &Default::default()).unwrap();
+    common::compare(content, &formatted);
+}
+
+#[test_log::test]
+fn presets_unknown() {
+    let options = sqlx_fmt::Options {
+        presets: Some("mysql-async".to_string()),
+        ..Default::default()
+    };
+    assert!(sqlx_fmt::format_with_options("", &options).is_err());
+    assert!(sqlx_fmt::validate_options(&options).is_err());
+}
@@ -0,0 +1,7 @@
+; string literals passed to `.execute(...)` methods, e.g. `client.execute("select 1")`
+(call_expression
+  function: (field_expression
+    field: (field_identifier) @f
+    (#eq? @f "execute"))
+  arguments: (arguments
+    [(string_literal) (raw_string_literal)] @sql))
@@ -0,0 +1,3 @@
+select   *
+    from
+        test where id = $1
@@ -0,0 +1,37 @@
+mod common;
+
+use std::path::Path;
+
+#[test_log::test]
+fn query_file() {
+    let content = r###"
+    sqlx::query_file!("tests/queries/query_file.sql", id)
+    sqlx::query_file_as!(Test, r#"tests/queries/query_file.sql"#, id)
+    sqlx::query_file!("tests/queries/missing.sql")
+    "###;
+
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .canonicalize()
+        .unwrap();
+    let options = sqlx_fmt::Options::default();
+
+    // the path literal itself is left alone
+
+    let formatted = sqlx_fmt::format_with_options(content, &options).unwrap();
+    common::compare(content, &formatted);
+
+    // the referenced file is resolved relative to the manifest dir
+
+    let sql_files =
+        sqlx_fmt::find_sql_files(content, &manifest_dir.join("src/lib.rs"), &options).unwrap();
+    assert_eq!(
+        sql_files,
+        vec![manifest_dir.join("tests/queries/query_file.sql")]
+    );
+
+    let sql = std::fs::read_to_string(&sql_files[0]).unwrap();
+    let formatted = sqlx_fmt::format_sql(&sql, &options).unwrap();
+
+    let expected = "select *\nfrom\n    test\nwhere id = $1\n";
+    common::compare(expected, &formatted);
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use sqlx_fmt::tree_sitter::SourceRange;
+
+const CONTENT: &str = r###"
+    let a = sqlx::query!("select *   from test where id = '1';");
+    let b = sqlx::query!(
+        "select *   from test where id = '1';"
+    );
+    let c = sqlx::query!("select *   from test where id = '1';");
+    "###;
+
+#[test_log::test]
+fn ranges_lines() {
+    let expected = r###"
+    let a = sqlx::query!("select *   from test where id = '1';");
+    let b = sqlx::query!(
+        "select * from test where id = '1';"
+    );
+    let c = sqlx::query!("select * from test where id = '1';");
+    "###;
+
+    let formatted = sqlx_fmt::format_with_options(
+        CONTENT,
+        &sqlx_fmt::Options {
+            ranges: vec!["3-4".parse().unwrap(), "6".parse().unwrap()],
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    common::compare(expected, &formatted);
+}
+
+#[test_log::test]
+fn ranges_bytes() {
+    let expected = r###"
+    let a = sqlx::query!("select * from test where id = '1';");
+    let b = sqlx::query!(
+        "select *   from test where id = '1';"
+    );
+    let c = sqlx::query!("select *   from test where id = '1';");
+    "###;
+
+    let start = CONTENT.find("select").unwrap();
+    let formatted = sqlx_fmt::format_with_options(
+        CONTENT,
+        &sqlx_fmt::Options {
+            ranges: vec![SourceRange::Bytes(start..start + 1)],
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    common::compare(expected, &formatted);
+}
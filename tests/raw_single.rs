@@ -3,7 +3,7 @@ mod common;
 #[test_log::test]
 fn raw_single() {
     let content = r###"
-    sqlx::migrate!(
+    sqlx::query!(
         r#"alter table    test add column log text"#,
     )
     "###;
@@ -11,7 +11,7 @@ fn raw_single() {
     let formatted = sqlx_fmt::format(content, ".sqruff", 4, &None).unwrap();
 
     let expected = r###"
-    sqlx::migrate!(
+    sqlx::query!(
         r#"alter table test add column log text"#,
     )
     "###;
@@ -3,7 +3,7 @@ mod common;
 #[test_log::test]
 fn raw_single_to_many() {
     let content = r###"
-    sqlx::migrate!(
+    sqlx::query!(
         r#"select *     from test where id = '1' and on = true and foo = 'bar' and foo = 'bar' and foo = 'bar' and foo = 'bar' and foo = 'bar'"#,
     )
     "###;
@@ -11,7 +11,7 @@ fn raw_single_to_many() {
     let formatted = sqlx_fmt::format(content, ".sqruff", 4, &None).unwrap();
 
     let expected = r###"
-    sqlx::migrate!(
+    sqlx::query!(
         r#"
             select *
             from test
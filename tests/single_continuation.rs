@@ -0,0 +1,79 @@
+mod common;
+
+#[test_log::test]
+fn single_continuation() {
+    let content = r###"
+        sqlx::query!("select   * \
+            from test \
+            where id = $1")
+    "###;
+
+    let expected = r###"
+        sqlx::query!("select * from test where id = $1")
+    "###;
+
+    let formatted = sqlx_fmt::format(content, ".sqruff", 4, &None).unwrap();
+    common::compare(expected, &formatted);
+}
+
+#[test_log::test]
+fn single_continuation_style() {
+    let content = r###"
+        sqlx::query!("select   * \
+            from test \
+            where id = $1")
+    "###;
+
+    let expected = r###"
+        sqlx::query!("select * \
+                      from \
+                          test \
+                      where id = $1")
+    "###;
+
+    let options = sqlx_fmt::Options {
+        string_style: sqlx_fmt::tree_sitter::StringStyle::Continuation,
+        ..Default::default()
+    };
+    let formatted = sqlx_fmt::format_with_options(content, &options).unwrap();
+    common::compare(expected, &formatted);
+}
+
+#[test_log::test]
+fn single_continuation_style_comment() {
+    let content = r###"
+        sqlx::query!("-- find the test
+            select   * \
+            from test \
+            where id = $1")
+    "###;
+
+    let expected = r###"
+        sqlx::query!("-- find the test\nselect *\nfrom\n    test\nwhere id = $1")
+    "###;
+
+    let options = sqlx_fmt::Options {
+        string_style: sqlx_fmt::tree_sitter::StringStyle::Continuation,
+        ..Default::default()
+    };
+    let formatted = sqlx_fmt::format_with_options(content, &options).unwrap();
+    common::compare(expected, &formatted);
+}
+
+#[test_log::test]
+fn single_continuation_style_quoted_line_break() {
+    let content = r###"
+        sqlx::query!("insert into t   (a) values ('l1\n    l2')")
+    "###;
+
+    let expected = r###"
+        sqlx::query!("insert into t (a) values ('l1\n    l2')")
+    "###;
+
+    let options = sqlx_fmt::Options {
+        string_style: sqlx_fmt::tree_sitter::StringStyle::Continuation,
+        ..Default::default()
+    };
+    let formatted = sqlx_fmt::format_with_options(content, &options).unwrap();
+    common::compare(expected, &formatted);
+}
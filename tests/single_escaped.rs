@@ -0,0 +1,43 @@
+mod common;
+
+#[test_log::test]
+fn single_escaped() {
+    let content = r###"
+        sqlx::query!("select 1 as   \"one!\" from test where name like 'a\\_%' and id = \u{27}1\x27;")
+    "###;
+
+    let expected = r###"
+        sqlx::query!("select 1 as \"one!\" from test where name like 'a\\_%' and id = '1';")
+    "###;
+
+    let formatted = sqlx_fmt::format(content, ".sqruff", 4, &None).unwrap();
+    common::compare(expected, &formatted);
+}
+
+#[test_log::test]
+fn single_escaped_comment() {
+    let content = r###"
+        sqlx::query!("-- find the test\nselect   * from test where id = $1")
+    "###;
+
+    let expected = r###"
+        sqlx::query!("-- find the test\nselect *\nfrom\n    test\nwhere id = $1")
+    "###;
+
+    let formatted = sqlx_fmt::format(content, ".sqruff", 4, &None).unwrap();
+    common::compare(expected, &formatted);
+}
+
+#[test_log::test]
+fn single_escaped_quoted_line_break() {
+    let content = r###"
+        sqlx::query!("insert into t   (a) values ('line1\nline2')")
+    "###;
+
+    let expected = r###"
+        sqlx::query!("insert into t (a) values ('line1\nline2')")
+    "###;
+
+    let formatted = sqlx_fmt::format(content, ".sqruff", 4, &None).unwrap();
+    common::compare(expected, &formatted);
+}
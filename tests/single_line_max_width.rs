@@ -0,0 +1,63 @@
+mod common;
+
+#[test_log::test]
+fn single_line_max_width() {
+    let content = r###"
+    let user = sqlx::query!(r#"select   *   from test"#);
+    let users = sqlx::query!(r#"select   *   from test where id = $1"#);
+    let query = sqlx::query!(
+        r#"
+        select   *
+            from   test
+        "#
+    );
+    "###;
+
+    let expected = r###"
+    let user = sqlx::query!(r#"select * from test"#);
+    let users = sqlx::query!(r#"
+                                 select *
+                                 from
+                                     test
+                                 where id = $1
+                             "#);
+    let query = sqlx::query!(
+        r#"select * from test"#
+    );
+    "###;
+
+    let formatted = sqlx_fmt::format_with_options(
+        content,
+        &sqlx_fmt::Options {
+            single_line_max_width: Some(60),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    common::compare(expected, &formatted);
+}
+
+#[test_log::test]
+fn single_line_max_width_quoted_line_break() {
+    let content = r###"
+    let user = sqlx::query!(r#"insert into t   values ('a
+b')"#);
+    "###;
+
+    let expected = r###"
+    let user = sqlx::query!(r#"
+                                insert into t values ('a
+                                b')
+                            "#);
+    "###;
+
+    let formatted = sqlx_fmt::format_with_options(
+        content,
+        &sqlx_fmt::Options {
+            single_line_max_width: Some(60),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    common::compare(expected, &formatted);
+}
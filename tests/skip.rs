@@ -0,0 +1,67 @@
+mod common;
+
+#[test_log::test]
+fn skip() {
+    let content = r###"
+    async fn queries(pool: &PgPool) {
+        // sqlx-fmt: skip
+        let a = sqlx::query!("select *   from test where id = '1';");
+        let b = sqlx::query!("select *   from test where id = '1';"); // sqlx-fmt: skip
+        let c = sqlx::query!("select *   from test where id = '1';");
+        let (d, e) = tokio::try_join!(
+            // sqlx-fmt: skip
+            sqlx::query!("select *   from test where id = '1';").fetch_one(pool),
+            sqlx::query!("select *   from test where id = '1';").fetch_one(pool),
+        );
+    }
+
+    #[rustfmt::skip]
+    async fn aligned() {
+        let a = sqlx::query!("select *   from test where id = '1';");
+    }
+
+    #[sqlx_fmt::skip]
+    async fn also_aligned() {
+        let a = sqlx::query!("select *   from test where id = '1';");
+    }
+    "###;
+
+    let expected = r###"
+    async fn queries(pool: &PgPool) {
+        // sqlx-fmt: skip
+        let a = sqlx::query!("select *   from test where id = '1';");
+        let b = sqlx::query!("select *   from test where id = '1';"); // sqlx-fmt: skip
+        let c = sqlx::query!("select * from test where id = '1';");
+        let (d, e) = tokio::try_join!(
+            // sqlx-fmt: skip
+            sqlx::query!("select *   from test where id = '1';").fetch_one(pool),
+            sqlx::query!("select * from test where id = '1';").fetch_one(pool),
+        );
+    }
+
+    #[rustfmt::skip]
+    async fn aligned() {
+        let a = sqlx::query!("select *   from test where id = '1';");
+    }
+
+    #[sqlx_fmt::skip]
+    async fn also_aligned() {
+        let a = sqlx::query!("select *   from test where id = '1';");
+    }
+    "###;
+
+    let formatted = sqlx_fmt::format(content, ".sqruff", 4, &None).unwrap();
+    common::compare(expected, &formatted);
+}
+
+#[test_log::test]
+fn skip_file() {
+    let content = r###"
+    //! sqlx-fmt: skip-file
+
+    let a = sqlx::query!("select *   from test where id = '1';");
+    "###;
+
+    let formatted = sqlx_fmt::format(content, ".sqruff", 4, &None).unwrap();
+    common::compare(content, &formatted);
+}
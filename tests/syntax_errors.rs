@@ -0,0 +1,62 @@
+mod common;
+
+#[test_log::test]
+fn syntax_errors() {
+    let content = r###"
+    async fn half_edited(pool: &PgPool) {
+        let a = sqlx::query!("select *   from test where id = '1';");
+        let b = a.
+    }
+
+    async fn other(pool: &PgPool) {
+        let c = sqlx::query!("select *   from test where id = '1';");
+    }
+    "###;
+
+    let expected = r###"
+    async fn half_edited(pool: &PgPool) {
+        let a = sqlx::query!("select *   from test where id = '1';");
+        let b = a.
+    }
+
+    async fn other(pool: &PgPool) {
+        let c = sqlx::query!("select * from test where id = '1';");
+    }
+    "###;
+
+    let formatted = sqlx_fmt::format(content, ".sqruff", 4, &None).unwrap();
+    common::compare(expected, &formatted);
+}
+
+#[test_log::test]
+fn syntax_errors_skipped() {
+    let content = r###"
+    async fn other(pool: &PgPool) {
+        let c = sqlx::query!("select *   from test where id = '1';");
+    }
+
+    async fn half_edited(pool: &PgPool) {
+        let a = sqlx::query!("select *   from test where id = '1';");
+        let b = a.
+    }
+    "###;
+
+    let formatted = sqlx_fmt::format_file(content, &Default::default()).unwrap();
+
+    // the sql next to the error is reported by its line
+
+    let skipped: Vec<_> = formatted
+        .skipped
+        .iter()
+        .map(|literal| literal.start_point.row + 1)
+        .collect();
+    assert_eq!(skipped, vec![7]);
+
+    // code after the formatted sql maps back to its place before formatting
+
+    let error = formatted.source.find("let b = a.").unwrap();
+    assert_eq!(formatted.original_offset(error), content.find("let b = a."));
+
+    let sql = formatted.source.find("select * from").unwrap();
+    assert_eq!(formatted.original_offset(sql), None);
+}
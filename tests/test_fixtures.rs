@@ -0,0 +1,50 @@
+mod common;
+
+use std::path::Path;
+
+#[test_log::test]
+fn test_fixtures() {
+    let content = r###"
+    #[sqlx::test(fixtures("test"))]
+    async fn by_name(pool: PgPool) {}
+
+    #[sqlx::test(fixtures("fixtures/other.sql"))]
+    async fn by_path(pool: PgPool) {}
+
+    #[sqlx::test(migrations = false, fixtures(path = "fixtures", scripts("test", "other")))]
+    async fn by_scripts(pool: PgPool) {}
+
+    #[test]
+    fn not_sqlx() {}
+    "###;
+
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .canonicalize()
+        .unwrap();
+    let options = sqlx_fmt::Options::default();
+
+    // fixtures are resolved relative to the rust file
+
+    let sql_files = sqlx_fmt::find_sql_files(
+        content,
+        &manifest_dir.join("tests/test_fixtures.rs"),
+        &options,
+    )
+    .unwrap();
+    assert_eq!(
+        sql_files
+            .iter()
+            .map(|p| p.canonicalize().unwrap())
+            .collect::<Vec<_>>(),
+        vec![
+            manifest_dir.join("tests/fixtures/other.sql"),
+            manifest_dir.join("tests/fixtures/test.sql"),
+        ]
+    );
+
+    let sql = std::fs::read_to_string(manifest_dir.join("tests/fixtures/test.sql")).unwrap();
+    let formatted = sqlx_fmt::format_sql(&sql, &options).unwrap();
+
+    let expected = "insert into test (id) values (1);\n";
+    common::compare(expected, &formatted);
+}
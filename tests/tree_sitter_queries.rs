@@ -0,0 +1,47 @@
+mod common;
+
+#[test_log::test]
+fn tree_sitter_queries() {
+    let content = r###"
+    client.execute("select *   from test where id = '1';").await?;
+    client.prepare("select *   from test where id = '1';").await?;
+
+    let rows = tokio::try_join!(
+        client.execute(
+            r#"
+                select   *
+                    from
+                        test where id = $1
+            "#,
+        ),
+    )?;
+    "###;
+
+    let expected = r###"
+    client.execute("select * from test where id = '1';").await?;
+    client.prepare("select *   from test where id = '1';").await?;
+
+    let rows = tokio::try_join!(
+        client.execute(
+            r#"
+                select *
+                from
+                    test
+                where id = $1
+            "#,
+        ),
+    )?;
+    "###;
+
+    let options = sqlx_fmt::Options {
+        tree_sitter_queries: Some("tests/queries/execute.scm".to_string()),
+        ..Default::default()
+    };
+    let formatted = sqlx_fmt::format_with_options(content, &options).unwrap();
+    common::compare(expected, &formatted);
+}
+
+#[test_log::test]
+fn tree_sitter_queries_without_sql_capture() {
+    assert!(sqlx_fmt::tree_sitter::parse_query("(string_literal) @literal").is_err());
+}
@@ -0,0 +1,46 @@
+mod common;
+
+#[test_log::test]
+fn unicode_columns() {
+    let content = r###"
+    let (emoji, query) = ("🦀🦀", sqlx::query!(r#"select   *   from test where id = $1"#));
+    let query = sqlx::query!(/* ñ */ r#"select   *   from test where id = $1"#);
+    "###;
+
+    let expected = r###"
+    let (emoji, query) = ("🦀🦀", sqlx::query!(r#"
+                                                   select *
+                                                   from
+                                                       test
+                                                   where id = $1
+                                               "#));
+    let query = sqlx::query!(/* ñ */ r#"
+                                         select *
+                                         from
+                                             test
+                                         where id = $1
+                                     "#);
+    "###;
+
+    let formatted = sqlx_fmt::format(content, ".sqruff", 4, &None).unwrap();
+    common::compare(expected, &formatted);
+}
+
+#[test_log::test]
+fn tab_columns() {
+    let content = "
+\tlet query = sqlx::query!(r#\"select   *   from test where id = $1\"#);
+";
+
+    let expected = "
+\tlet query = sqlx::query!(r#\"
+\t                             select *
+\t                             from
+\t                                 test
+\t                             where id = $1
+\t                         \"#);
+";
+
+    let formatted = sqlx_fmt::format(content, ".sqruff", 4, &None).unwrap();
+    common::compare(expected, &formatted);
+}
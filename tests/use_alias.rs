@@ -0,0 +1,39 @@
+mod common;
+
+#[test_log::test]
+fn use_alias() {
+    let content = r###"
+    use sqlx::query_as as qa;
+    use ::sqlx as db;
+
+    mod repo {
+        pub use sqlx::{query, query_scalar as scalar};
+    }
+
+    qa!(Test, "select *   from test where id = '1';");
+    ::sqlx::query!("select *   from test where id = '1';");
+    db::query_scalar!("select *   from test where id = '1';");
+    repo::scalar!("select *   from test where id = '1';");
+    crate::repo::query!("select *   from test where id = '1';");
+    other::query!("select *   from test where id = '1';");
+    "###;
+
+    let expected = r###"
+    use sqlx::query_as as qa;
+    use ::sqlx as db;
+
+    mod repo {
+        pub use sqlx::{query, query_scalar as scalar};
+    }
+
+    qa!(Test, "select * from test where id = '1';");
+    ::sqlx::query!("select * from test where id = '1';");
+    db::query_scalar!("select * from test where id = '1';");
+    repo::scalar!("select * from test where id = '1';");
+    crate::repo::query!("select * from test where id = '1';");
+    other::query!("select *   from test where id = '1';");
+    "###;
+
+    let formatted = sqlx_fmt::format(content, ".sqruff", 4, &None).unwrap();
+    common::compare(expected, &formatted);
+}